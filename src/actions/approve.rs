@@ -0,0 +1,208 @@
+use anyhow::bail;
+use ethers::prelude::*;
+use futures::future::join_all;
+use lazy_static::lazy_static;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Mutex;
+
+use crate::{
+    actions::{get_fees::to_typed_transaction, send_transaction::send_from},
+    config::{get_config, get_private_keys},
+    models::{chain::get_chain_id, wallet::BlazingWallet},
+    simulation::simulator::get_simulator,
+    utils::key_to_address::private_key_to_address,
+    worker::Worker,
+};
+
+lazy_static! {
+    // approve snipers come from the same key pool for every worker, so the sets overlap and
+    // each wallet is approved by the first worker that claims it. Another worker's sender is
+    // never claimed, its nonces belong to that worker's pre-signed buys.
+    static ref CLAIMED_APPROVE_SNIPERS: Mutex<HashSet<Address>> = Mutex::new(HashSet::new());
+}
+
+pub enum Approval {
+    Sent,
+    AlreadyApproved,
+    // dry run, the approve was only simulated
+    Simulated,
+}
+
+pub struct ApproveReport {
+    pub spender: Address,
+    pub ready: Vec<Address>,
+    pub already_approved: Vec<Address>,
+    pub simulated: Vec<Address>,
+    pub failed: Vec<(Address, String)>,
+}
+
+impl Worker {
+    // buy.approveTo is either "contract" (the BuyBot) or anything else for the router
+    pub fn approve_spender(&self) -> Address {
+        if get_config().buy.approve_to == "contract" {
+            self.buy_bot.address
+        } else {
+            self.router.address
+        }
+    }
+
+    pub fn get_approve_transaction(&self, wallet: &BlazingWallet) -> Eip1559TransactionRequest {
+        let config = get_config();
+
+        Eip1559TransactionRequest::new()
+            .data(
+                self.purchase_token
+                    .contract
+                    .approve(self.approve_spender(), U256::MAX)
+                    .calldata()
+                    .unwrap(),
+            )
+            .to(self.purchase_token.address)
+            .from(wallet.address)
+            .gas(config.approve_gas.gas_limit)
+            .max_fee_per_gas(config.approve_gas.max_fee_per_gas)
+            .max_priority_fee_per_gas(config.approve_gas.max_priority_fee_per_gas)
+    }
+
+    pub async fn pre_approve(&self) -> ApproveReport {
+        let spender = self.approve_spender();
+
+        // nobody can hold more than the total supply, so that much allowance is always enough
+        let total_supply = self
+            .purchase_token
+            .contract
+            .total_supply()
+            .call()
+            .await
+            .unwrap_or(U256::MAX);
+
+        let other_senders = self.other_worker_senders();
+
+        let wallets = {
+            let mut claimed = CLAIMED_APPROVE_SNIPERS.lock().unwrap();
+
+            self.snipers
+                .approve_snipers
+                .iter()
+                .filter(|wallet| !other_senders.contains(&wallet.address))
+                .filter(|wallet| claimed.insert(wallet.address))
+                .collect::<Vec<_>>()
+        };
+
+        let results = join_all(
+            wallets
+                .iter()
+                .map(|wallet| self.approve(wallet, spender, total_supply)),
+        )
+        .await;
+
+        let mut report = ApproveReport {
+            spender,
+            ready: vec![],
+            already_approved: vec![],
+            simulated: vec![],
+            failed: vec![],
+        };
+
+        for (wallet, result) in wallets.iter().zip(results) {
+            match result {
+                Ok(Approval::Sent) => report.ready.push(wallet.address),
+                Ok(Approval::AlreadyApproved) => report.already_approved.push(wallet.address),
+                Ok(Approval::Simulated) => report.simulated.push(wallet.address),
+                Err(error) => report.failed.push((wallet.address, error.to_string())),
+            }
+        }
+
+        report
+    }
+
+    // Worker N sends from the Nth private key
+    fn other_worker_senders(&self) -> HashSet<Address> {
+        get_private_keys()
+            .iter()
+            .take(get_config().workers)
+            .map(private_key_to_address)
+            .filter(|address| *address != self.wallet.address)
+            .collect()
+    }
+
+    async fn approve(
+        &self,
+        wallet: &BlazingWallet,
+        spender: Address,
+        total_supply: U256,
+    ) -> anyhow::Result<Approval> {
+        let allowance = self
+            .purchase_token
+            .contract
+            .allowance(wallet.address, spender)
+            .call()
+            .await?;
+
+        if allowance >= total_supply {
+            return Ok(Approval::AlreadyApproved);
+        }
+
+        let transaction = to_typed_transaction(
+            self.get_approve_transaction(wallet)
+                .chain_id(get_chain_id().await),
+        );
+
+        let outcome = {
+            let simulator = get_simulator().await;
+            let mut simulator = simulator.lock().await;
+
+            simulator.simulate(&transaction)
+        };
+
+        if !outcome.is_success() {
+            bail!("approve simulation failed: {}", outcome);
+        }
+
+        if get_config().dry_run {
+            return Ok(Approval::Simulated);
+        }
+
+        let report = send_from(wallet, transaction).await?;
+
+        if !report.success {
+            bail!("approve {:?} reverted", report.tx_hash);
+        }
+
+        Ok(Approval::Sent)
+    }
+}
+
+impl fmt::Display for ApproveReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Approvals to {:?}: {} approved, {} already approved",
+            self.spender,
+            self.ready.len(),
+            self.already_approved.len()
+        )?;
+
+        if !self.simulated.is_empty() {
+            write!(f, ", {} not approved (dry run)", self.simulated.len())?;
+        }
+
+        for (address, error) in self.failed.iter() {
+            write!(f, "\n  {:?} failed: {}", address, error)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ApproveReport {
+    // wallets that can sell the purchase token through the spender
+    pub fn ready_to_sell(&self) -> Vec<Address> {
+        self.ready
+            .iter()
+            .chain(self.already_approved.iter())
+            .cloned()
+            .collect()
+    }
+}
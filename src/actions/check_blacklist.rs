@@ -0,0 +1,85 @@
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::keccak256;
+use lazy_static::lazy_static;
+
+use crate::{
+    simulation::{block_env::SECONDS_PER_BLOCK, simulator::Simulator},
+    worker::Worker,
+};
+
+// far enough past the buy for per-block transfer delays and cooldowns to have expired
+const PROBE_BLOCKS_AFTER_BUY: u64 = 10;
+
+lazy_static! {
+    // an address no token has seen, so max wallet limits and exclusions don't apply to it
+    static ref PROBE_RECIPIENT: Address =
+        Address::from_slice(&keccak256("blazing-bot blacklist probe")[12..]);
+}
+
+impl Worker {
+    // Snipers that receive tokens from the buy but can't move them afterwards
+    pub fn check_blacklist(
+        &self,
+        simulator: &mut Simulator,
+        buy_transaction: &TypedTransaction,
+    ) -> anyhow::Result<Vec<Address>> {
+        let block = simulator.evm.env.block.clone();
+        let snapshot = simulator.snapshot();
+        let blacklisted = self.simulate_transfers(simulator, buy_transaction);
+        simulator.revert(snapshot);
+        simulator.evm.env.block = block;
+
+        blacklisted
+    }
+
+    fn simulate_transfers(
+        &self,
+        simulator: &mut Simulator,
+        buy_transaction: &TypedTransaction,
+    ) -> anyhow::Result<Vec<Address>> {
+        simulator.simulate_commit(buy_transaction);
+
+        let block = &mut simulator.evm.env.block;
+        block.number += revm::primitives::U256::from(PROBE_BLOCKS_AFTER_BUY);
+        block.timestamp += revm::primitives::U256::from(PROBE_BLOCKS_AFTER_BUY * SECONDS_PER_BLOCK);
+
+        let mut blacklisted = vec![];
+
+        for sniper in self.snipers.recipients.iter() {
+            let balance =
+                self.balance_of(simulator, &self.purchase_token.contract, sniper.address)?;
+
+            if balance.is_zero() {
+                continue;
+            }
+
+            // a small transfer stays under max transaction limits
+            let amount = std::cmp::max(balance / 100, U256::one());
+
+            let outcome = simulator.call_from(
+                sniper.address,
+                self.purchase_token.address,
+                self.purchase_token
+                    .contract
+                    .transfer(*PROBE_RECIPIENT, amount)
+                    .calldata()
+                    .unwrap(),
+            );
+
+            // some tokens return false instead of reverting
+            let returned_false =
+                outcome.output.len() == 32 && outcome.output.iter().all(|byte| *byte == 0);
+
+            if !outcome.is_success() || returned_false {
+                println!(
+                    "{:?} can't transfer after the buy: {}",
+                    sniper.address, outcome
+                );
+                blacklisted.push(sniper.address);
+            }
+        }
+
+        Ok(blacklisted)
+    }
+}
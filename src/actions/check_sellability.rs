@@ -0,0 +1,193 @@
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use std::fmt;
+
+use crate::{
+    actions::simulate_buy::BuyReport,
+    config::get_config,
+    simulation::simulator::Simulator,
+    utils::{decimals::Decimals, revm_types::from_revm_u256},
+    worker::Worker,
+};
+
+const SELL_DEADLINE_SECONDS: u64 = 60;
+
+pub struct SellabilityReport {
+    pub seller: Option<Address>,
+    pub buy_tax: f64,
+    pub sell_tax: f64,
+    pub failure: Option<String>,
+}
+
+impl SellabilityReport {
+    fn failed(seller: Option<Address>, failure: String) -> Self {
+        SellabilityReport {
+            seller,
+            buy_tax: 0.0,
+            sell_tax: 100.0,
+            failure: Some(failure),
+        }
+    }
+
+    pub fn is_sellable(&self) -> bool {
+        let config = get_config();
+
+        self.failure.is_none()
+            && self.buy_tax <= config.buy.max_buy_tax as f64
+            && self.sell_tax <= config.buy.max_sell_tax as f64
+    }
+}
+
+impl Worker {
+    // buy -> approve -> sell for the first sniper that receives tokens, all in one snapshot
+    // of the fork
+    pub fn check_sellability(
+        &self,
+        simulator: &mut Simulator,
+        buy_transaction: &TypedTransaction,
+    ) -> SellabilityReport {
+        let snapshot = simulator.snapshot();
+        let checked = self.simulate_buy_and_sell(simulator, buy_transaction);
+        simulator.revert(snapshot);
+
+        let (mut buy_report, mut report) = match checked {
+            Ok(checked) => checked,
+            Err(error) => return SellabilityReport::failed(None, error.to_string()),
+        };
+
+        // quoted against the pre-buy state restored above
+        match self.quote_purchase_token(simulator, buy_report.chain_token_spent) {
+            Ok(expected) => {
+                buy_report.purchase_token_expected = expected;
+                report.buy_tax = buy_report.effective_buy_tax();
+            }
+            Err(error) => {
+                return SellabilityReport::failed(
+                    report.seller,
+                    format!("buy quote failed: {}", error),
+                )
+            }
+        }
+
+        report
+    }
+
+    fn simulate_buy_and_sell(
+        &self,
+        simulator: &mut Simulator,
+        buy_transaction: &TypedTransaction,
+    ) -> anyhow::Result<(BuyReport, SellabilityReport)> {
+        let buy_report = self.buy_report(simulator, buy_transaction)?;
+
+        if !buy_report.outcome.is_success() {
+            let failure = format!("buy failed: {}", buy_report.outcome);
+            return Ok((buy_report, SellabilityReport::failed(None, failure)));
+        }
+
+        let seller = buy_report
+            .snipers
+            .iter()
+            .find(|sniper| sniper.purchase_token_after > sniper.purchase_token_before)
+            .map(|sniper| sniper.address);
+
+        let report = match seller {
+            Some(seller) => self.simulate_sell(simulator, seller)?,
+            None => SellabilityReport::failed(None, "no tokens received".to_string()),
+        };
+
+        Ok((buy_report, report))
+    }
+
+    // Approve and sell run without fees, the seller doesn't need any ETH for gas
+    fn simulate_sell(
+        &self,
+        simulator: &mut Simulator,
+        seller: Address,
+    ) -> anyhow::Result<SellabilityReport> {
+        let amount = self.balance_of(simulator, &self.purchase_token.contract, seller)?;
+        let chain_token_before = self.balance_of(simulator, &self.chain_token.contract, seller)?;
+
+        let path = self.get_sell_path();
+
+        let expected = self.quote(simulator, amount, path.clone())?;
+
+        let deadline = from_revm_u256(simulator.evm.env.block.timestamp) + SELL_DEADLINE_SECONDS;
+
+        let approve_outcome = simulator.call_commit_from(
+            seller,
+            self.purchase_token.address,
+            self.purchase_token
+                .contract
+                .approve(self.router.address, U256::MAX)
+                .calldata()
+                .unwrap(),
+        );
+
+        if !approve_outcome.is_success() {
+            return Ok(SellabilityReport::failed(
+                Some(seller),
+                format!("approve failed: {}", approve_outcome),
+            ));
+        }
+
+        let sell_outcome = simulator.call_commit_from(
+            seller,
+            self.router.address,
+            self.router
+                .contract
+                .swap_exact_tokens_for_tokens_supporting_fee_on_transfer_tokens(
+                    amount,
+                    U256::zero(),
+                    path,
+                    seller,
+                    deadline,
+                )
+                .calldata()
+                .unwrap(),
+        );
+
+        if !sell_outcome.is_success() {
+            return Ok(SellabilityReport::failed(
+                Some(seller),
+                format!("sell failed: {}", sell_outcome),
+            ));
+        }
+
+        let received = self
+            .balance_of(simulator, &self.chain_token.contract, seller)?
+            .saturating_sub(chain_token_before);
+
+        let sell_tax = if expected.is_zero() {
+            0.0
+        } else {
+            expected.saturating_sub(received).from_decimals(0) / expected.from_decimals(0) * 100.0
+        };
+
+        Ok(SellabilityReport {
+            seller: Some(seller),
+            buy_tax: 0.0,
+            sell_tax,
+            failure: None,
+        })
+    }
+}
+
+impl fmt::Display for SellabilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Buy tax: {:.2}%, sell tax: {:.2}%",
+            self.buy_tax, self.sell_tax
+        )?;
+
+        if let Some(seller) = self.seller {
+            write!(f, ", seller: {:?}", seller)?;
+        }
+
+        if let Some(failure) = &self.failure {
+            write!(f, ", {}", failure)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,11 +1,31 @@
 use crate::{config::get_config, utils::decimals::Decimals, worker::Worker};
+use ethers::abi::{self, Token};
 use ethers::prelude::*;
+use ethers::utils::keccak256;
 
 impl Worker {
-    pub fn get_buy_transaction(&self) -> Eip1559TransactionRequest {
+    // keccak256(idSalt, worker index, round), unique per worker and round
+    pub fn get_txid(&self, round: u8) -> U256 {
         let config = get_config();
 
-        let txid = U256::from(0);
+        if !config.buy.use_txid {
+            return U256::zero();
+        }
+
+        let packed = abi::encode_packed(&[
+            Token::String(config.buy.id_salt.clone()),
+            Token::Uint(U256::from(self.index)),
+            Token::Uint(U256::from(round)),
+        ])
+        .unwrap();
+
+        U256::from_big_endian(&keccak256(packed))
+    }
+
+    pub fn get_buy_transaction(&self, round: u8) -> Eip1559TransactionRequest {
+        let config = get_config();
+
+        let txid = self.get_txid(round);
 
         let buy_amounts = vec![
             // todo decimals should be read from the token
@@ -0,0 +1,169 @@
+use anyhow::anyhow;
+use crossterm::style::Stylize;
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use std::cmp::{max, min};
+
+use super::sell::multiply_gas;
+use crate::{
+    config::{get_config, FeePolicy},
+    models::chain::get_chain,
+    utils::decimals::Decimals,
+    worker::Worker,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Fees {
+    // of the next block
+    pub basefee: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
+
+impl Fees {
+    // check.skemGwei.limit is a hard ceiling on the max fee
+    pub fn capped(mut self) -> Self {
+        let skem_gwei = &get_config().check.skem_gwei;
+
+        if skem_gwei.enabled {
+            let limit = (skem_gwei.limit as f64).to_decimals(9);
+
+            if self.max_fee_per_gas > limit {
+                println!(
+                    "{} max fee {} capped to skemGwei limit {}",
+                    "Warning:".yellow(),
+                    self.max_fee_per_gas,
+                    limit
+                );
+
+                self.max_fee_per_gas = limit;
+            }
+        }
+
+        self.max_priority_fee_per_gas = min(self.max_priority_fee_per_gas, self.max_fee_per_gas);
+
+        self
+    }
+
+    pub fn apply(&self, request: Eip1559TransactionRequest) -> TypedTransaction {
+        to_typed_transaction(
+            request
+                .max_fee_per_gas(self.max_fee_per_gas)
+                .max_priority_fee_per_gas(self.max_priority_fee_per_gas),
+        )
+    }
+}
+
+// A legacy transaction paying the max fee when eip1559 is off. The basefee it was computed
+// from is stale by the time the transaction lands, so it isn't taken off.
+pub fn to_typed_transaction(request: Eip1559TransactionRequest) -> TypedTransaction {
+    if get_config().eip1559 {
+        return request.into();
+    }
+
+    TransactionRequest {
+        from: request.from,
+        to: request.to,
+        gas: request.gas,
+        gas_price: request.max_fee_per_gas,
+        value: request.value,
+        data: request.data,
+        nonce: request.nonce,
+        chain_id: request.chain_id,
+    }
+    .into()
+}
+
+impl Worker {
+    pub async fn get_basefee(&self) -> anyhow::Result<U256> {
+        let block = get_chain()
+            .await
+            .get_block(BlockNumber::Latest)
+            .await?
+            .ok_or_else(|| anyhow!("latest block not found"))?;
+
+        Ok(block
+            .next_block_base_fee()
+            .unwrap_or(block.base_fee_per_gas.unwrap_or_default()))
+    }
+
+    // Buy fees from feeStrategy.policy, `dev_transaction` is what matchDev follows
+    pub async fn get_buy_fees(
+        &self,
+        dev_transaction: Option<&Transaction>,
+    ) -> anyhow::Result<Fees> {
+        let config = get_config();
+        let strategy = &config.fee_strategy;
+
+        let basefee = self.get_basefee().await?;
+
+        let max_priority_fee_per_gas = match strategy.policy {
+            FeePolicy::Static => config.gas.max_priority_fee_per_gas,
+            FeePolicy::BasefeeMultiple => {
+                let history = get_chain()
+                    .await
+                    .fee_history(
+                        strategy.fee_history_blocks,
+                        BlockNumber::Latest,
+                        &[strategy.fee_history_percentile],
+                    )
+                    .await?;
+
+                let rewards = history
+                    .reward
+                    .iter()
+                    .filter_map(|reward| reward.first().cloned())
+                    .collect::<Vec<_>>();
+
+                let average = if rewards.is_empty() {
+                    U256::zero()
+                } else {
+                    rewards
+                        .iter()
+                        .fold(U256::zero(), |total, reward| total + reward)
+                        / rewards.len()
+                };
+
+                max(config.gas.max_priority_fee_per_gas, average)
+            }
+            FeePolicy::MatchDev => {
+                let dev_priority_fee = dev_transaction
+                    .map(|transaction| {
+                        transaction.max_priority_fee_per_gas.unwrap_or_else(|| {
+                            transaction
+                                .gas_price
+                                .unwrap_or_default()
+                                .saturating_sub(basefee)
+                        })
+                    })
+                    .unwrap_or_default();
+
+                dev_priority_fee + strategy.priority_fee_delta
+            }
+        };
+
+        let max_fee_per_gas = match strategy.policy {
+            FeePolicy::Static => config.gas.max_fee_per_gas,
+            _ => multiply_gas(basefee, strategy.basefee_multiplier) + max_priority_fee_per_gas,
+        };
+
+        let fees = Fees {
+            basefee,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        }
+        .capped();
+
+        println!(
+            "Worker #{}: {:?} fees, basefee: {}, max fee: {}, max priority fee: {}{}",
+            self.index,
+            strategy.policy,
+            fees.basefee,
+            fees.max_fee_per_gas,
+            fees.max_priority_fee_per_gas,
+            if config.eip1559 { "" } else { " (legacy)" }
+        );
+
+        Ok(fees)
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{config::get_config, worker::Worker};
+use ethers::prelude::*;
+
+impl Worker {
+    // purchase token -> (liquidity token) -> chain token
+    pub fn get_sell_path(&self) -> Vec<Address> {
+        let mut path = vec![self.purchase_token.address];
+
+        if self.liquidity_token.address != self.chain_token.address {
+            path.push(self.liquidity_token.address);
+        }
+
+        path.push(self.chain_token.address);
+
+        path
+    }
+
+    pub fn get_sell_transaction(&self, sellers: Vec<Address>) -> Eip1559TransactionRequest {
+        let config = get_config();
+
+        Eip1559TransactionRequest::new()
+            .data(
+                self.buy_bot
+                    .contract
+                    .sell_de_gainzz(
+                        // RouterAddress
+                        self.router.address,
+                        // PurchaseTokenAddress
+                        self.purchase_token.address,
+                        // LiquidityTokenAddress
+                        self.liquidity_token.address,
+                        // Sellers
+                        sellers,
+                        // Percentage
+                        U256::from(config.sell.sell_percentage),
+                        // Amount, 0 sells the percentage of each seller's balance
+                        U256::from(0),
+                    )
+                    .calldata()
+                    .unwrap(),
+            )
+            .to(self.buy_bot.address)
+    }
+
+    // Sold by the seller itself when buy.approveTo is the router
+    pub fn get_router_sell_transaction(
+        &self,
+        seller: Address,
+        amount: U256,
+        deadline: U256,
+    ) -> Eip1559TransactionRequest {
+        Eip1559TransactionRequest::new()
+            .data(
+                self.router
+                    .contract
+                    .swap_exact_tokens_for_tokens_supporting_fee_on_transfer_tokens(
+                        amount,
+                        U256::zero(),
+                        self.get_sell_path(),
+                        seller,
+                        deadline,
+                    )
+                    .calldata()
+                    .unwrap(),
+            )
+            .to(self.router.address)
+            .from(seller)
+    }
+}
@@ -1 +1,12 @@
+pub mod approve;
+pub mod check_blacklist;
+pub mod check_sellability;
 pub mod get_buy_transaction;
+pub mod get_fees;
+pub mod get_sell_transaction;
+pub mod pregen_transactions;
+pub mod sell;
+pub mod send_bundle;
+pub mod send_transaction;
+pub mod simulate_bundle;
+pub mod simulate_buy;
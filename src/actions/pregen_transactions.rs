@@ -0,0 +1,157 @@
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use std::cmp::max;
+
+use super::get_fees::Fees;
+use crate::{config::get_config, models::chain::get_chain_id, worker::Worker};
+
+pub struct SignedTransaction {
+    pub max_priority_fee_per_gas: U256,
+    pub transaction: TypedTransaction,
+    pub raw: Bytes,
+    pub hash: H256,
+}
+
+pub struct PregenLadder {
+    pub nonce: U256,
+    // sorted by max_priority_fee_per_gas ascending
+    pub transactions: Vec<SignedTransaction>,
+}
+
+impl PregenLadder {
+    // cheapest step paying at least `max_priority_fee_per_gas`, the top step if none does
+    pub fn pick(&self, max_priority_fee_per_gas: U256) -> Option<&SignedTransaction> {
+        self.transactions
+            .iter()
+            .find(|signed| signed.max_priority_fee_per_gas >= max_priority_fee_per_gas)
+            .or_else(|| self.transactions.last())
+    }
+}
+
+impl Worker {
+    // `ladder` unless the wallet's pending nonce moved away from it since it was signed,
+    // e.g. after a transaction sent outside the bot
+    pub async fn current_ladder<'a>(
+        &self,
+        ladder: Option<&'a PregenLadder>,
+    ) -> anyhow::Result<Option<&'a PregenLadder>> {
+        let ladder = match ladder {
+            Some(ladder) => ladder,
+            None => return Ok(None),
+        };
+
+        let pending_nonce = self
+            .wallet
+            .signer
+            .get_transaction_count(self.wallet.address, Some(BlockNumber::Pending.into()))
+            .await?;
+
+        if pending_nonce != ladder.nonce {
+            println!(
+                "Worker #{}: pre-signed buy nonce {} is stale, pending nonce is {}, signing it fresh",
+                self.index, ladder.nonce, pending_nonce
+            );
+            return Ok(None);
+        }
+
+        Ok(Some(ladder))
+    }
+
+    // The ladder step for the policy `fees` and the simulated `gas_limit`, sent as signed.
+    // None when no step pays the priority fee, can be included at the basefee or has enough
+    // gas, the buy is signed fresh then.
+    pub fn pick_pre_signed<'a>(
+        &self,
+        ladder: &'a PregenLadder,
+        fees: &Fees,
+        gas_limit: U256,
+    ) -> Option<&'a SignedTransaction> {
+        let signed = ladder.pick(fees.max_priority_fee_per_gas)?;
+        let max_fee_per_gas = signed.transaction.gas_price().unwrap_or_default();
+        let signed_gas_limit = signed.transaction.gas().cloned().unwrap_or_default();
+
+        if signed.max_priority_fee_per_gas < fees.max_priority_fee_per_gas
+            || max_fee_per_gas < fees.basefee + fees.max_priority_fee_per_gas
+        {
+            println!(
+                "Worker #{}: pre-signed buy (max fee {}, max priority fee {}) doesn't fit the fees, signing it fresh",
+                self.index, max_fee_per_gas, signed.max_priority_fee_per_gas
+            );
+            return None;
+        }
+
+        if signed_gas_limit < gas_limit {
+            println!(
+                "Worker #{}: pre-signed buy gas limit {} is below the simulated {}, signing it fresh",
+                self.index, signed_gas_limit, gas_limit
+            );
+            return None;
+        }
+
+        Some(signed)
+    }
+
+    // One ladder per buy.rounds, round N signed with the pending nonce + N
+    pub async fn pregen_buy_transactions(&self) -> anyhow::Result<Vec<PregenLadder>> {
+        let config = get_config();
+
+        let pending_nonce = self
+            .wallet
+            .signer
+            .get_transaction_count(self.wallet.address, Some(BlockNumber::Pending.into()))
+            .await?;
+
+        let chain_id = get_chain_id().await;
+        let basefee = self.get_basefee().await?;
+        let mut ladders = vec![];
+
+        for round in 0..config.buy.rounds {
+            let nonce = pending_nonce + round;
+            let mut transactions = vec![];
+
+            for max_priority_fee_per_gas in config.check.pregen.max_priority_fee_per_gas.clone() {
+                let fees = Fees {
+                    basefee,
+                    max_fee_per_gas: max(config.gas.max_fee_per_gas, max_priority_fee_per_gas),
+                    max_priority_fee_per_gas,
+                }
+                .capped();
+
+                let transaction = fees.apply(
+                    self.get_buy_transaction(round)
+                        .from(self.wallet.address)
+                        .nonce(nonce)
+                        .gas(config.gas.gas_limit)
+                        .chain_id(chain_id),
+                );
+
+                let signature = self.wallet.local.sign_transaction_sync(&transaction)?;
+
+                transactions.push(SignedTransaction {
+                    max_priority_fee_per_gas,
+                    raw: transaction.rlp_signed(&signature),
+                    hash: transaction.hash(&signature),
+                    transaction,
+                });
+            }
+
+            ladders.push(PregenLadder {
+                nonce,
+                transactions,
+            });
+        }
+
+        println!(
+            "Worker #{}: pre-signed {} rounds of {} buy transactions from nonce {}",
+            self.index,
+            ladders.len(),
+            ladders
+                .first()
+                .map(|ladder| ladder.transactions.len())
+                .unwrap_or_default(),
+            pending_nonce
+        );
+
+        Ok(ladders)
+    }
+}
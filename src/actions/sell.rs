@@ -0,0 +1,232 @@
+use anyhow::bail;
+use bigdecimal::ToPrimitive;
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use futures::future::join_all;
+
+use crate::{
+    actions::{get_fees::Fees, send_transaction::send_from},
+    config::get_config,
+    models::{chain::get_chain_id, wallet::BlazingWallet},
+    simulation::{gas::estimate_gas_limit, simulator::get_simulator},
+    worker::Worker,
+};
+
+const SELL_DEADLINE_SECONDS: i64 = 60;
+
+impl Worker {
+    // (max fee, max priority fee) of the last buy scaled by sell.gasMultiplier, the configured
+    // buy gas when nothing was bought yet
+    pub fn get_sell_fees(&self) -> (U256, U256) {
+        let config = get_config();
+
+        let (max_fee_per_gas, max_priority_fee_per_gas) = match *self.buy_fees.lock().unwrap() {
+            Some(fees) => (fees.max_fee_per_gas, fees.max_priority_fee_per_gas),
+            None => (
+                config.gas.max_fee_per_gas,
+                config.gas.max_priority_fee_per_gas,
+            ),
+        };
+
+        (
+            multiply_gas(max_fee_per_gas, config.sell.gas_multiplier),
+            multiply_gas(max_priority_fee_per_gas, config.sell.gas_multiplier),
+        )
+    }
+
+    pub async fn sell(&self) -> anyhow::Result<bool> {
+        let recipients = self
+            .snipers
+            .recipients
+            .iter()
+            .map(|sniper| sniper.address)
+            .collect();
+
+        self.sell_wallets(recipients, self.get_sell_fees()).await
+    }
+
+    // Sells sell.sellPercentage of the purchase token held by `wallets`, simulated on a fork
+    // of the latest block first. Returns whether anything was sold.
+    pub async fn sell_wallets(
+        &self,
+        wallets: Vec<Address>,
+        fees: (U256, U256),
+    ) -> anyhow::Result<bool> {
+        self.sell_approved(wallets, fees, true).await
+    }
+
+    // Same as `sell_wallets` without the simulation, for sells racing a pending transaction
+    pub async fn emergency_sell_wallets(
+        &self,
+        wallets: Vec<Address>,
+        fees: (U256, U256),
+    ) -> anyhow::Result<bool> {
+        self.sell_approved(wallets, fees, false).await
+    }
+
+    async fn sell_approved(
+        &self,
+        wallets: Vec<Address>,
+        (max_fee_per_gas, max_priority_fee_per_gas): (U256, U256),
+        simulate: bool,
+    ) -> anyhow::Result<bool> {
+        let config = get_config();
+
+        let sellers = self.approved_balances(wallets).await;
+
+        if sellers.is_empty() {
+            println!("Worker #{}: nothing to sell", self.index);
+            return Ok(false);
+        }
+
+        let fees = Fees {
+            basefee: self.get_basefee().await?,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        }
+        .capped();
+
+        let chain_id = get_chain_id().await;
+
+        // buy.approveTo "contract" sells every seller in one BuyBot transaction, otherwise
+        // each seller swaps its own tokens on the router
+        let mut transactions: Vec<(&BlazingWallet, TypedTransaction)> =
+            if self.approve_spender() == self.buy_bot.address {
+                let sellers = sellers.iter().map(|(seller, _)| *seller).collect();
+
+                vec![(
+                    &*self.wallet,
+                    fees.apply(
+                        self.get_sell_transaction(sellers)
+                            .from(self.wallet.address)
+                            .gas(config.gas.gas_limit)
+                            .chain_id(chain_id),
+                    ),
+                )]
+            } else {
+                let deadline = U256::from(chrono::Utc::now().timestamp() + SELL_DEADLINE_SECONDS);
+
+                sellers
+                    .iter()
+                    .filter_map(|(seller, balance)| {
+                        let wallet = self.sniper_wallet(*seller)?;
+                        let amount = *balance * config.sell.sell_percentage / 100;
+
+                        Some((
+                            wallet,
+                            fees.apply(
+                                self.get_router_sell_transaction(*seller, amount, deadline)
+                                    .gas(config.gas.gas_limit)
+                                    .chain_id(chain_id),
+                            ),
+                        ))
+                    })
+                    .collect()
+            };
+
+        if simulate {
+            let simulator = get_simulator().await;
+            let mut simulator = simulator.lock().await;
+
+            // the snipers only hold what was bought after the fork was taken
+            simulator.refork().await?;
+
+            for (wallet, transaction) in transactions.iter_mut() {
+                let outcome = simulator.simulate(transaction);
+
+                println!("Sell simulation for {:?}: {}", wallet.address, outcome);
+
+                if !outcome.is_success() {
+                    bail!("sell simulation failed: {}", outcome);
+                }
+
+                transaction.set_gas(estimate_gas_limit(&outcome, &config.gas));
+            }
+        }
+
+        if config.dry_run {
+            println!("Dry run, not sending the sell");
+            return Ok(false);
+        }
+
+        let reports = join_all(
+            transactions
+                .into_iter()
+                .map(|(wallet, transaction)| send_from(wallet, transaction)),
+        )
+        .await;
+
+        let mut sold = false;
+
+        for report in reports {
+            let report = report?;
+
+            println!("Sell: {}", report);
+
+            if !report.success {
+                bail!("sell transaction {:?} reverted", report.tx_hash);
+            }
+
+            sold = true;
+        }
+
+        Ok(sold)
+    }
+
+    // Purchase token balances of the `wallets` that hold some and allow the spender to move
+    // all of it
+    async fn approved_balances(&self, wallets: Vec<Address>) -> Vec<(Address, U256)> {
+        let spender = self.approve_spender();
+
+        let balances = join_all(wallets.iter().map(|wallet| async move {
+            let balance = self
+                .purchase_token
+                .contract
+                .balance_of(*wallet)
+                .call()
+                .await?;
+            let allowance = self
+                .purchase_token
+                .contract
+                .allowance(*wallet, spender)
+                .call()
+                .await?;
+
+            Ok::<_, ContractError<Provider<Ws>>>((balance, allowance))
+        }))
+        .await;
+
+        wallets
+            .into_iter()
+            .zip(balances)
+            .filter_map(|(wallet, balances)| match balances {
+                Ok((balance, _)) if balance.is_zero() => None,
+                Ok((balance, allowance)) if allowance < balance => {
+                    println!(
+                        "Worker #{}: {:?} isn't approved for {:?}, not selling it",
+                        self.index, wallet, spender
+                    );
+                    None
+                }
+                Ok((balance, _)) => Some((wallet, balance)),
+                Err(_) => None,
+            })
+            .collect()
+    }
+
+    fn sniper_wallet(&self, address: Address) -> Option<&BlazingWallet> {
+        self.snipers
+            .recipients
+            .iter()
+            .chain(self.snipers.approve_snipers.iter())
+            .find(|wallet| wallet.address == address)
+    }
+}
+
+pub fn multiply_gas(gas: U256, multiplier: f64) -> U256 {
+    U256::from(
+        (gas.as_u128() as f64 * multiplier)
+            .to_u128()
+            .expect("multiply_gas(): Failed to convert f64 to u128"),
+    )
+}
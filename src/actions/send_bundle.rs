@@ -0,0 +1,144 @@
+use anyhow::{anyhow, bail};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::keccak256;
+
+use super::get_fees::to_typed_transaction;
+use super::pregen_transactions::SignedTransaction;
+use super::send_transaction::{guard_send, SendReport};
+use crate::{
+    config::get_config,
+    mev::bundle::{get_searcher, send_bundle, Bundle},
+    models::chain::{get_chain, get_chain_id},
+    utils::decimals::Decimals,
+    worker::Worker,
+};
+
+// the bundle is resubmitted for each of this many blocks after the current one
+const TARGET_BLOCKS: u64 = 3;
+
+impl Worker {
+    // The signed buy followed by a BuyBot.sendBribe of mev.bribeAmount when it isn't zero
+    pub async fn get_bundle_transactions(
+        &self,
+        buy: TypedTransaction,
+        signed_buy: Option<&SignedTransaction>,
+    ) -> anyhow::Result<Vec<(TypedTransaction, Bytes)>> {
+        let config = get_config();
+
+        let (buy, raw_buy) = match signed_buy {
+            Some(signed) => (signed.transaction.clone(), signed.raw.clone()),
+            None => {
+                let mut buy = buy;
+
+                let nonce = self
+                    .wallet
+                    .signer
+                    .get_transaction_count(self.wallet.address, Some(BlockNumber::Pending.into()))
+                    .await?;
+                buy.set_nonce(nonce);
+
+                let signature = self.wallet.local.sign_transaction_sync(&buy)?;
+                let raw = buy.rlp_signed(&signature);
+
+                (buy, raw)
+            }
+        };
+
+        let nonce = *buy.nonce().expect("bundle buy has no nonce");
+        let max_fee_per_gas = buy.gas_price().unwrap_or_default();
+
+        let mut transactions = vec![(buy, raw_buy)];
+
+        if config.mev.bribe_amount > 0.0 {
+            let bribe = to_typed_transaction(
+                Eip1559TransactionRequest::new()
+                    .from(self.wallet.address)
+                    .to(self.buy_bot.address)
+                    .data(self.buy_bot.contract.send_bribe().calldata().unwrap())
+                    .value(config.mev.bribe_amount.to_decimals(18))
+                    .nonce(nonce + 1)
+                    .gas(config.approve_gas.gas_limit)
+                    .max_fee_per_gas(max_fee_per_gas)
+                    .max_priority_fee_per_gas(0)
+                    .chain_id(get_chain_id().await),
+            );
+
+            let signature = self.wallet.local.sign_transaction_sync(&bribe)?;
+            let raw_bribe = bribe.rlp_signed(&signature);
+
+            transactions.push((bribe, raw_bribe));
+        }
+
+        Ok(transactions)
+    }
+
+    // Submits the bundle for the next TARGET_BLOCKS blocks and reports the buy once one of
+    // them includes it
+    pub async fn send_bundle(
+        &self,
+        transactions: Vec<(TypedTransaction, Bytes)>,
+    ) -> anyhow::Result<SendReport> {
+        guard_send(async {
+            let chain = get_chain().await;
+            let searcher = get_searcher()?;
+
+            let buy_hash = transactions
+                .first()
+                .map(|(_, raw)| H256::from(keccak256(raw)))
+                .ok_or_else(|| anyhow!("empty bundle"))?;
+
+            let raw_transactions = transactions
+                .into_iter()
+                .map(|(_, raw)| raw)
+                .collect::<Vec<_>>();
+
+            let mut blocks = chain.subscribe_blocks().await?;
+            let current_block = chain.get_block_number().await?;
+            let last_block = current_block + TARGET_BLOCKS;
+
+            for block_number in current_block.as_u64() + 1..=last_block.as_u64() {
+                let bundle = Bundle {
+                    transactions: raw_transactions.clone(),
+                    block_number: block_number.into(),
+                };
+
+                for submission in send_bundle(searcher, &bundle).await {
+                    println!("Worker #{}: {}", self.index, submission);
+                }
+            }
+
+            println!(
+                "Worker #{}: bundle with {:?} targets blocks {} to {}",
+                self.index,
+                buy_hash,
+                current_block + 1,
+                last_block
+            );
+
+            while let Some(block) = blocks.next().await {
+                let block_number = block.number.unwrap_or_default();
+
+                if let Some(receipt) = chain.get_transaction_receipt(buy_hash).await? {
+                    return Ok(SendReport::from_receipt(buy_hash, receipt));
+                }
+
+                println!(
+                    "Worker #{}: bundle not included in block {}",
+                    self.index, block_number
+                );
+
+                if block_number >= last_block {
+                    break;
+                }
+            }
+
+            bail!(
+                "bundle with {:?} wasn't included by block {}",
+                buy_hash,
+                last_block
+            )
+        })
+        .await
+    }
+}
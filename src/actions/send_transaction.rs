@@ -0,0 +1,111 @@
+use anyhow::{anyhow, bail};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::{config::get_config, models::wallet::BlazingWallet, worker::Worker};
+
+// set once any live transaction fails, checked before sending when `stopAfterFirstFail` is on
+static SEND_FAILED: AtomicBool = AtomicBool::new(false);
+
+pub struct SendReport {
+    pub tx_hash: H256,
+    pub block_number: Option<U64>,
+    pub gas_used: Option<U256>,
+    pub success: bool,
+    pub logs: Vec<Log>,
+}
+
+impl Worker {
+    pub async fn send_transaction(
+        &self,
+        transaction: TypedTransaction,
+    ) -> anyhow::Result<SendReport> {
+        guard_send(send_from(&self.wallet, transaction)).await
+    }
+
+    pub async fn send_raw_transaction(&self, raw: Bytes) -> anyhow::Result<SendReport> {
+        guard_send(async {
+            let pending = self.wallet.signer.send_raw_transaction(raw).await?;
+            wait_for_receipt(pending).await
+        })
+        .await
+    }
+}
+
+// unguarded send, for transactions that shouldn't count towards `stopAfterFirstFail`
+pub async fn send_from(
+    wallet: &BlazingWallet,
+    transaction: TypedTransaction,
+) -> anyhow::Result<SendReport> {
+    let pending = wallet.signer.send_transaction(transaction, None).await?;
+
+    wait_for_receipt(pending).await
+}
+
+pub async fn guard_send<F>(send: F) -> anyhow::Result<SendReport>
+where
+    F: Future<Output = anyhow::Result<SendReport>>,
+{
+    let config = get_config();
+
+    if config.stop_after_first_fail && SEND_FAILED.load(Ordering::SeqCst) {
+        bail!("a previous transaction failed and stopAfterFirstFail is set");
+    }
+
+    let report = send.await;
+
+    if !matches!(report, Ok(SendReport { success: true, .. })) {
+        SEND_FAILED.store(true, Ordering::SeqCst);
+    }
+
+    report
+}
+
+async fn wait_for_receipt(pending: PendingTransaction<'_, Ws>) -> anyhow::Result<SendReport> {
+    let config = get_config();
+
+    let pending = pending.interval(Duration::from_millis(config.websocket_polling_ms));
+    let tx_hash = *pending;
+
+    println!("Sent {:?}, waiting for receipt", tx_hash);
+
+    let receipt = tokio::time::timeout(Duration::from_millis(config.receipt_timeout_ms), pending)
+        .await
+        .map_err(|_| anyhow!("timed out waiting for the receipt of {:?}", tx_hash))??
+        .ok_or_else(|| anyhow!("{:?} was dropped from the mempool", tx_hash))?;
+
+    Ok(SendReport::from_receipt(tx_hash, receipt))
+}
+
+impl SendReport {
+    pub fn from_receipt(tx_hash: H256, receipt: TransactionReceipt) -> Self {
+        SendReport {
+            tx_hash,
+            block_number: receipt.block_number,
+            gas_used: receipt.gas_used,
+            success: receipt.status == Some(U64::from(1)),
+            logs: receipt.logs,
+        }
+    }
+}
+
+impl fmt::Display for SendReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} {} in block {}, gas used: {}",
+            self.tx_hash,
+            if self.success {
+                "succeeded"
+            } else {
+                "reverted"
+            },
+            self.block_number.unwrap_or_default(),
+            self.gas_used.unwrap_or_default()
+        )
+    }
+}
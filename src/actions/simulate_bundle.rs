@@ -0,0 +1,42 @@
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+
+use crate::{
+    monitors::dev_action::DevTrigger,
+    simulation::{outcome::BundleOutcome, simulator::get_simulator, tx_env::from_transaction},
+    worker::Worker,
+};
+
+impl Worker {
+    // The dev transaction goes first unless the simulator already contains it
+    pub async fn simulate_bundle(
+        &self,
+        dev_trigger: &DevTrigger,
+        bundle: &[(TypedTransaction, Bytes)],
+    ) -> BundleOutcome {
+        let simulator = get_simulator().await;
+        let mut simulator = simulator.lock().await;
+
+        let mut transactions = vec![];
+
+        if !simulator.contains(&dev_trigger.watched.transaction) {
+            transactions.push(from_transaction(&dev_trigger.watched.transaction));
+        }
+
+        // signed with our pending nonce, which the fork block can be behind of, so the
+        // simulator fills in the nonce it expects
+        transactions.extend(bundle.iter().map(|(transaction, _)| {
+            let mut transaction = transaction.clone();
+
+            match &mut transaction {
+                TypedTransaction::Legacy(transaction) => transaction.nonce = None,
+                TypedTransaction::Eip2930(transaction) => transaction.tx.nonce = None,
+                TypedTransaction::Eip1559(transaction) => transaction.nonce = None,
+            }
+
+            transaction
+        }));
+
+        simulator.simulate_bundle(&transactions)
+    }
+}
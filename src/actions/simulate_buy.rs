@@ -0,0 +1,249 @@
+use anyhow::bail;
+use crossterm::style::Stylize;
+use ethers::abi::{self, ParamType, Token};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::format_units;
+use std::fmt;
+
+use crate::{
+    simulation::{outcome::SimulationOutcome, simulator::Simulator},
+    utils::decimals::Decimals,
+    worker::Worker,
+};
+
+pub struct SniperBalanceDelta {
+    pub address: Address,
+    pub purchase_token_before: U256,
+    pub purchase_token_after: U256,
+    pub chain_token_before: U256,
+    pub chain_token_after: U256,
+}
+
+pub struct BuyReport {
+    pub outcome: SimulationOutcome,
+    pub snipers: Vec<SniperBalanceDelta>,
+    pub chain_token_spent: U256,
+    pub purchase_token_received: U256,
+    pub purchase_token_expected: U256,
+    pub purchase_token_decimals: u8,
+    pub chain_token_decimals: u8,
+}
+
+impl BuyReport {
+    // percentage of the router quote for the spent amount that didn't reach the snipers
+    pub fn effective_buy_tax(&self) -> f64 {
+        if self.purchase_token_expected.is_zero() {
+            return 0.0;
+        }
+
+        let missing = self
+            .purchase_token_expected
+            .saturating_sub(self.purchase_token_received);
+
+        missing.from_decimals(0) / self.purchase_token_expected.from_decimals(0) * 100.0
+    }
+}
+
+impl Worker {
+    pub fn simulate_buy(
+        &self,
+        simulator: &mut Simulator,
+        transaction: &TypedTransaction,
+    ) -> anyhow::Result<BuyReport> {
+        let snapshot = simulator.snapshot();
+        let report = self.buy_report(simulator, transaction);
+        simulator.revert(snapshot);
+
+        let mut report = report?;
+
+        // quoted against the pre-buy state restored above
+        report.purchase_token_expected =
+            self.quote_purchase_token(simulator, report.chain_token_spent)?;
+
+        Ok(report)
+    }
+
+    // Commits the buy, `purchase_token_expected` is left for the caller to quote
+    pub fn buy_report(
+        &self,
+        simulator: &mut Simulator,
+        transaction: &TypedTransaction,
+    ) -> anyhow::Result<BuyReport> {
+        let before = self.sniper_balances(simulator)?;
+        let bot_chain_token_before =
+            self.balance_of(simulator, &self.chain_token.contract, self.buy_bot.address)?;
+
+        let outcome = simulator.simulate_commit(transaction);
+
+        let after = self.sniper_balances(simulator)?;
+        let bot_chain_token_after =
+            self.balance_of(simulator, &self.chain_token.contract, self.buy_bot.address)?;
+
+        let snipers = before
+            .into_iter()
+            .zip(after)
+            .map(
+                |((address, purchase_before, chain_before), (_, purchase_after, chain_after))| {
+                    SniperBalanceDelta {
+                        address,
+                        purchase_token_before: purchase_before,
+                        purchase_token_after: purchase_after,
+                        chain_token_before: chain_before,
+                        chain_token_after: chain_after,
+                    }
+                },
+            )
+            .collect::<Vec<_>>();
+
+        let chain_token_spent = bot_chain_token_before.saturating_sub(bot_chain_token_after);
+        let purchase_token_received = snipers.iter().fold(U256::zero(), |total, sniper| {
+            total
+                + sniper
+                    .purchase_token_after
+                    .saturating_sub(sniper.purchase_token_before)
+        });
+
+        Ok(BuyReport {
+            outcome,
+            snipers,
+            chain_token_spent,
+            purchase_token_received,
+            purchase_token_expected: U256::zero(),
+            purchase_token_decimals: self.purchase_token.decimals,
+            chain_token_decimals: self.chain_token.decimals,
+        })
+    }
+
+    fn sniper_balances(
+        &self,
+        simulator: &mut Simulator,
+    ) -> anyhow::Result<Vec<(Address, U256, U256)>> {
+        self.snipers
+            .recipients
+            .iter()
+            .map(|sniper| {
+                Ok((
+                    sniper.address,
+                    self.balance_of(simulator, &self.purchase_token.contract, sniper.address)?,
+                    self.balance_of(simulator, &self.chain_token.contract, sniper.address)?,
+                ))
+            })
+            .collect()
+    }
+
+    pub fn balance_of(
+        &self,
+        simulator: &mut Simulator,
+        token: &crate::abis::IERC20<Provider<Ws>>,
+        owner: Address,
+    ) -> anyhow::Result<U256> {
+        let output =
+            simulator.call(token.address(), token.balance_of(owner).calldata().unwrap())?;
+
+        match abi::decode(&[ParamType::Uint(256)], &output)?
+            .into_iter()
+            .next()
+        {
+            Some(Token::Uint(balance)) => Ok(balance),
+            _ => bail!("balanceOf({:?}) returned {}", owner, output),
+        }
+    }
+
+    pub fn quote_purchase_token(
+        &self,
+        simulator: &mut Simulator,
+        chain_token_amount: U256,
+    ) -> anyhow::Result<U256> {
+        if chain_token_amount.is_zero() {
+            return Ok(U256::zero());
+        }
+
+        let mut path = vec![self.chain_token.address];
+
+        if self.liquidity_token.address != self.chain_token.address {
+            path.push(self.liquidity_token.address);
+        }
+
+        path.push(self.purchase_token.address);
+
+        self.quote(simulator, chain_token_amount, path)
+    }
+
+    pub fn quote(
+        &self,
+        simulator: &mut Simulator,
+        amount: U256,
+        path: Vec<Address>,
+    ) -> anyhow::Result<U256> {
+        let output = simulator.call(
+            self.router.address,
+            self.router
+                .contract
+                .get_amounts_out(amount, path)
+                .calldata()
+                .unwrap(),
+        )?;
+
+        let amounts = abi::decode(&[ParamType::Array(Box::new(ParamType::Uint(256)))], &output)?;
+
+        match amounts.into_iter().next() {
+            Some(Token::Array(amounts)) => match amounts.last() {
+                Some(Token::Uint(amount)) => Ok(*amount),
+                _ => bail!("getAmountsOut returned no amounts"),
+            },
+            _ => bail!("getAmountsOut returned {}", output),
+        }
+    }
+}
+
+// Signed balance change, exact for any amount
+fn format_delta(before: U256, after: U256, decimals: u8) -> String {
+    let (sign, delta) = if after >= before {
+        ('+', after - before)
+    } else {
+        ('-', before - after)
+    };
+
+    match format_units(delta, decimals as u32) {
+        Ok(delta) => format!("{}{}", sign, delta),
+        Err(_) => format!("{}{}", sign, delta),
+    }
+}
+
+impl fmt::Display for BuyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for sniper in self.snipers.iter() {
+            let received = if sniper.purchase_token_after > sniper.purchase_token_before {
+                format!("{:?}", sniper.address).green()
+            } else {
+                format!("{:?}", sniper.address).red()
+            };
+
+            writeln!(
+                f,
+                "{} purchase token: {}, chain token: {}",
+                received,
+                format_delta(
+                    sniper.purchase_token_before,
+                    sniper.purchase_token_after,
+                    self.purchase_token_decimals
+                ),
+                format_delta(
+                    sniper.chain_token_before,
+                    sniper.chain_token_after,
+                    self.chain_token_decimals
+                ),
+            )?;
+        }
+
+        write!(
+            f,
+            "Chain token spent by BuyBot: {}, purchase token received: {} (expected {}), effective buy tax: {:.2}%",
+            self.chain_token_spent.from_decimals(self.chain_token_decimals),
+            self.purchase_token_received.from_decimals(self.purchase_token_decimals),
+            self.purchase_token_expected.from_decimals(self.purchase_token_decimals),
+            self.effective_buy_tax()
+        )
+    }
+}
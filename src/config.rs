@@ -1,5 +1,6 @@
 use crossterm::style::Stylize;
 use ethers::prelude::{k256::SecretKey, *};
+use once_cell::sync::OnceCell;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde::{
     de::{DeserializeOwned, Error},
@@ -35,6 +36,21 @@ where
     Ok(vec_secret_key)
 }
 
+fn deserialize_option_secret_key<'de, D>(deserializer: D) -> Result<Option<SecretKey>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(s) => {
+            let bytes = hex::decode(s.trim_start_matches("0x")).map_err(D::Error::custom)?;
+            Ok(Some(
+                SecretKey::from_slice(&bytes).map_err(D::Error::custom)?,
+            ))
+        }
+        None => Ok(None),
+    }
+}
+
 fn deserialize_gas<'de, D>(deserializer: D) -> Result<U256, D::Error>
 where
     D: Deserializer<'de>,
@@ -102,11 +118,27 @@ where
     map.end()
 }
 
+fn default_true() -> bool {
+    true
+}
+
+fn default_receipt_timeout_ms() -> u64 {
+    60_000
+}
+
+fn default_gas_limit_margin() -> u64 {
+    20
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Keys {
     #[serde(deserialize_with = "deserialize_vec_secret_key")]
     pub private_keys: Vec<SecretKey>,
+
+    // signs mev bundles, never holds funds
+    #[serde(default, deserialize_with = "deserialize_option_secret_key")]
+    pub searcher_key: Option<SecretKey>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -128,13 +160,26 @@ pub struct Config {
     pub buy: BuyConfig,
     pub gas: GasConfig,
     pub approve_gas: GasConfig,
+
+    #[serde(default)]
+    pub fee_strategy: FeeStrategyConfig,
     pub stop_after_first_fail: bool,
+
+    // simulate only, never broadcast
+    #[serde(default = "default_true")]
+    pub dry_run: bool,
+
+    #[serde(default = "default_receipt_timeout_ms")]
+    pub receipt_timeout_ms: u64,
     pub sell: SellConfig,
     pub dev_action: DevActionConfig,
     pub check: CheckConfig,
     pub w_token_amount_for_buybot_tax_checks: f64,
     pub blocks_delay_before_first_buy: u8,
     pub wait_before_first_buy_m_s: f64,
+
+    #[serde(default)]
+    pub simulation: SimulationConfig,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -188,6 +233,48 @@ pub struct GasConfig {
 
     #[serde(deserialize_with = "deserialize_gas")]
     pub max_priority_fee_per_gas: U256,
+
+    // percent added on top of the simulated gas, the result is capped by gas_limit
+    #[serde(default = "default_gas_limit_margin")]
+    pub gas_limit_margin: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FeePolicy {
+    // gas.maxFeePerGas and gas.maxPriorityFeePerGas as configured
+    Static,
+    // priority fee from the fee history, max fee a multiple of the basefee on top of it
+    BasefeeMultiple,
+    // the dev transaction's priority fee plus priorityFeeDelta
+    MatchDev,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FeeStrategyConfig {
+    pub policy: FeePolicy,
+
+    // max fee = basefee * basefeeMultiplier + priority fee, except for the static policy
+    pub basefee_multiplier: f64,
+
+    #[serde(deserialize_with = "deserialize_gas")]
+    pub priority_fee_delta: U256,
+
+    pub fee_history_blocks: u64,
+    pub fee_history_percentile: f64,
+}
+
+impl Default for FeeStrategyConfig {
+    fn default() -> Self {
+        FeeStrategyConfig {
+            policy: FeePolicy::Static,
+            basefee_multiplier: 2.0,
+            priority_fee_delta: 1_f64.to_decimals(9),
+            fee_history_blocks: 10,
+            fee_history_percentile: 50.0,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -236,6 +323,29 @@ pub struct MevConfig {
     pub endpoints: Vec<Endpoint>,
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationConfig {
+    // latest block when not set
+    pub fork_block_number: Option<u64>,
+
+    #[serde(default)]
+    pub block_overrides: BlockOverridesConfig,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOverridesConfig {
+    // simulate as if this many blocks were mined after the fork block
+    #[serde(default)]
+    pub blocks_after_fork: u64,
+
+    #[serde(default)]
+    pub timestamp_offset: u64,
+
+    pub basefee: Option<f64>, // gwei
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Endpoint {
@@ -320,26 +430,33 @@ pub struct GasStepsConfig {
     #[serde(deserialize_with = "deserialize_gas")]
     pub step: U256,
 
+    // starts at `from` on the first call to `next`
     #[serde(skip)]
-    current: U256,
+    current: Option<U256>,
 }
 
 impl Iterator for GasStepsConfig {
     type Item = U256;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current > self.to {
+        let current = self.current.unwrap_or(self.from);
+
+        if current > self.to {
             None
         } else {
-            let result = self.current;
-            self.current += self.step;
-            Some(result)
+            self.current = Some(if self.step.is_zero() {
+                self.to + 1
+            } else {
+                current + self.step
+            });
+            Some(current)
         }
     }
 }
 
 static mut CONFIG: Option<Config> = None;
 static mut PRIVATE_KEYS: Option<Vec<SecretKey>> = None;
+static SEARCHER_KEY: OnceCell<Option<SecretKey>> = OnceCell::new();
 pub static mut CONFIG_JSON: Value = Value::Null;
 
 impl Config {
@@ -365,11 +482,18 @@ pub fn get_private_keys() -> &'static Vec<SecretKey> {
             }
 
             PRIVATE_KEYS = Some(keys.private_keys);
+            SEARCHER_KEY.set(keys.searcher_key).ok();
             PRIVATE_KEYS.as_ref().unwrap()
         }
     }
 }
 
+pub fn get_searcher_key() -> Option<&'static SecretKey> {
+    get_private_keys();
+
+    SEARCHER_KEY.get().and_then(Option::as_ref)
+}
+
 pub fn get_config() -> &'static Config {
     unsafe {
         if let Some(config) = &CONFIG {
@@ -399,6 +523,24 @@ pub fn get_config() -> &'static Config {
                 );
                 process::exit(1);
             }
+
+            if config.mev.enabled && config.mev.endpoints.is_empty() {
+                eprintln!(
+                    "{} set mev.endpoints or mev.enabled: false",
+                    "Mev is enabled without any endpoint to send bundles to,".red()
+                );
+                process::exit(1);
+            }
+
+            if config.mev.enabled && get_searcher_key().is_none() {
+                eprintln!(
+                    "{}{}",
+                    "Mev is enabled without a searcherKey in".red(),
+                    " pkeys.json".yellow()
+                );
+                process::exit(1);
+            }
+
             CONFIG = Some(config);
             CONFIG.as_ref().unwrap()
         }
@@ -414,3 +556,59 @@ where
 
     Ok(t)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gas_steps(json: &str) -> GasStepsConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn gwei(value: f64) -> U256 {
+        value.to_decimals(9)
+    }
+
+    #[test]
+    fn gas_steps_include_from_and_to() {
+        let steps = gas_steps(r#"{ "from": 1, "to": 2, "step": 0.5 }"#);
+
+        assert_eq!(
+            steps.collect::<Vec<_>>(),
+            vec![gwei(1.0), gwei(1.5), gwei(2.0)]
+        );
+    }
+
+    #[test]
+    fn gas_steps_stop_before_overshooting_to() {
+        let steps = gas_steps(r#"{ "from": 1, "to": 2, "step": 0.4 }"#);
+
+        assert_eq!(
+            steps.collect::<Vec<_>>(),
+            vec![gwei(1.0), gwei(1.4), gwei(1.8)]
+        );
+    }
+
+    #[test]
+    fn zero_gas_step_yields_from_once() {
+        let steps = gas_steps(r#"{ "from": 3, "to": 5, "step": 0 }"#);
+
+        assert_eq!(steps.collect::<Vec<_>>(), vec![gwei(3.0)]);
+    }
+
+    #[test]
+    fn gas_steps_are_empty_when_from_is_above_to() {
+        let steps = gas_steps(r#"{ "from": 5, "to": 3, "step": 1 }"#);
+
+        assert_eq!(steps.count(), 0);
+    }
+
+    #[test]
+    fn cloned_gas_steps_restart_at_from() {
+        let steps = gas_steps(r#"{ "from": 1, "to": 2, "step": 1 }"#);
+
+        // every round iterates its own clone of the config
+        assert_eq!(steps.clone().count(), 2);
+        assert_eq!(steps.clone().count(), 2);
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::anyhow;
+use ethers::prelude::*;
+use ethers::utils::keccak256;
+use futures::future::join_all;
+use lazy_static::lazy_static;
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+use crate::config::{get_config, get_searcher_key, Endpoint};
+
+const SIGNATURE_HEADER: &str = "X-Flashbots-Signature";
+
+lazy_static! {
+    // a key of its own so the relays' searcher reputation isn't tied to a trading wallet
+    static ref SEARCHER: Option<LocalWallet> = get_searcher_key().cloned().map(LocalWallet::from);
+}
+
+pub fn get_searcher() -> anyhow::Result<&'static LocalWallet> {
+    SEARCHER
+        .as_ref()
+        .ok_or_else(|| anyhow!("no searcherKey in pkeys.json to sign bundles with"))
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bundle {
+    #[serde(rename = "txs")]
+    pub transactions: Vec<Bytes>,
+    pub block_number: U64,
+}
+
+#[derive(Serialize)]
+struct JsonRpcRequest<'a> {
+    jsonrpc: &'a str,
+    id: u64,
+    method: &'a str,
+    params: [&'a Bundle; 1],
+}
+
+pub struct BundleSubmission {
+    pub endpoint: String,
+    pub block_number: U64,
+    // bundle hash returned by the endpoint
+    pub result: anyhow::Result<H256>,
+}
+
+// eth_sendBundle to every mev.endpoints entry, signed by `searcher` the way Flashbots
+// expects: personal_sign over the hex keccak256 of the request body
+pub async fn send_bundle(searcher: &LocalWallet, bundle: &Bundle) -> Vec<BundleSubmission> {
+    send_bundle_to(&get_config().mev.endpoints, searcher, bundle).await
+}
+
+async fn send_bundle_to(
+    endpoints: &[Endpoint],
+    searcher: &LocalWallet,
+    bundle: &Bundle,
+) -> Vec<BundleSubmission> {
+    let body = serde_json::to_string(&JsonRpcRequest {
+        jsonrpc: "2.0",
+        id: 1,
+        method: "eth_sendBundle",
+        params: [bundle],
+    })
+    .unwrap();
+
+    let signature = match searcher
+        .sign_message(format!("{:?}", H256::from(keccak256(&body))))
+        .await
+    {
+        Ok(signature) => format!("{:?}:0x{}", searcher.address(), signature),
+        Err(error) => {
+            return endpoints
+                .iter()
+                .map(|endpoint| BundleSubmission {
+                    endpoint: endpoint.endpoint.clone(),
+                    block_number: bundle.block_number,
+                    result: Err(anyhow!("failed to sign the bundle: {}", error)),
+                })
+                .collect()
+        }
+    };
+
+    let client = reqwest::Client::new();
+
+    join_all(endpoints.iter().map(|endpoint| {
+        let request = post(&client, endpoint, body.clone(), &signature);
+
+        async move {
+            BundleSubmission {
+                endpoint: endpoint.endpoint.clone(),
+                block_number: bundle.block_number,
+                result: request.await,
+            }
+        }
+    }))
+    .await
+}
+
+async fn post(
+    client: &reqwest::Client,
+    endpoint: &Endpoint,
+    body: String,
+    signature: &str,
+) -> anyhow::Result<H256> {
+    let response = client
+        .post(&endpoint.endpoint)
+        .headers(endpoint.headers.clone().unwrap_or_default())
+        .header(CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await?
+        .text()
+        .await?;
+
+    let response: Value = serde_json::from_str(&response)
+        .map_err(|_| anyhow!("unexpected response: {}", response))?;
+
+    if let Some(error) = response.get("error") {
+        return Err(anyhow!("{}", error));
+    }
+
+    let bundle_hash = response["result"]["bundleHash"]
+        .as_str()
+        .ok_or_else(|| anyhow!("no bundle hash in {}", response))?;
+
+    Ok(bundle_hash.parse()?)
+}
+
+impl fmt::Display for BundleSubmission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.result {
+            Ok(bundle_hash) => write!(
+                f,
+                "{} accepted bundle {:?} for block {}",
+                self.endpoint, bundle_hash, self.block_number
+            ),
+            Err(error) => write!(
+                f,
+                "{} rejected the bundle for block {}: {}",
+                self.endpoint, self.block_number, error
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const BUNDLE_HASH: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+
+    // Accepts one request, replies with BUNDLE_HASH and returns (headers, body)
+    async fn serve_once(listener: TcpListener) -> (Vec<(String, String)>, String) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = vec![];
+
+        let (head, body_start) = loop {
+            let mut buffer = [0; 4096];
+            let read = stream.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+
+            if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                break (String::from_utf8(request[..end].to_vec()).unwrap(), end + 4);
+            }
+        };
+
+        let headers = head
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+            .collect::<Vec<_>>();
+
+        let content_length = headers
+            .iter()
+            .find(|(name, _)| name == "content-length")
+            .map(|(_, value)| value.parse::<usize>().unwrap())
+            .unwrap();
+
+        while request.len() < body_start + content_length {
+            let mut buffer = [0; 4096];
+            let read = stream.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+        }
+
+        let body = String::from_utf8(request[body_start..].to_vec()).unwrap();
+
+        let response = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"result":{{"bundleHash":"{}"}}}}"#,
+            BUNDLE_HASH
+        );
+        stream
+            .write_all(
+                format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+
+        (headers, body)
+    }
+
+    #[tokio::test]
+    async fn sends_a_signed_eth_send_bundle() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = Endpoint {
+            endpoint: format!("http://{}", listener.local_addr().unwrap()),
+            headers: None,
+        };
+        let server = tokio::spawn(serve_once(listener));
+
+        let searcher = LocalWallet::new(&mut rand::thread_rng());
+        let bundle = Bundle {
+            transactions: vec![Bytes::from(vec![0x02, 0xaa]), Bytes::from(vec![0x02, 0xbb])],
+            block_number: U64::from(17_000_000),
+        };
+
+        let submissions = send_bundle_to(&[endpoint], &searcher, &bundle).await;
+        let (headers, body) = server.await.unwrap();
+
+        assert_eq!(submissions.len(), 1);
+        assert_eq!(
+            *submissions[0].result.as_ref().unwrap(),
+            H256::from_str(BUNDLE_HASH).unwrap()
+        );
+
+        let request: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(request["method"], "eth_sendBundle");
+        assert_eq!(request["params"][0]["txs"][0], "0x02aa");
+        assert_eq!(request["params"][0]["txs"][1], "0x02bb");
+        assert_eq!(request["params"][0]["blockNumber"], "0x1036640");
+
+        let signature_header = headers
+            .iter()
+            .find(|(name, _)| *name == SIGNATURE_HEADER.to_lowercase())
+            .map(|(_, value)| value.clone())
+            .expect("no signature header");
+        let (address, signature) = signature_header.split_once(':').unwrap();
+
+        assert_eq!(address, format!("{:?}", searcher.address()));
+
+        let signature = Signature::from_str(signature.trim_start_matches("0x")).unwrap();
+        let signer = signature
+            .recover(format!("{:?}", H256::from(keccak256(&body))))
+            .unwrap();
+
+        assert_eq!(signer, searcher.address());
+    }
+}
@@ -0,0 +1,76 @@
+use crossterm::style::Stylize;
+use ethers::abi::Token;
+use ethers::prelude::*;
+
+use super::mempool::{watch_transactions, WatchedTransaction};
+use crate::{abis::BLACKLIST_ABI, models::chain::get_chain, worker::Worker};
+
+impl Worker {
+    // Sells the snipers targeted by a blacklist-style call to the purchase token
+    pub async fn watch_blacklist(&self) -> anyhow::Result<bool> {
+        let chain = get_chain().await;
+        let mut transactions = Box::pin(watch_transactions(&chain).await?);
+
+        println!(
+            "Watching {:?} for blacklist calls",
+            self.purchase_token.address
+        );
+
+        while let Some(watched) = transactions.next().await {
+            let targets = self.blacklist_targets(&watched);
+
+            if targets.is_empty() {
+                continue;
+            }
+
+            println!(
+                "{} {}, selling {:?}",
+                "Blacklist call".red(),
+                watched,
+                targets
+            );
+
+            return self
+                .emergency_sell_wallets(targets, self.outbid_fees(&watched.transaction))
+                .await;
+        }
+
+        Ok(false)
+    }
+
+    // Our snipers passed to one of the Blacklist.json functions, a false flag (un-blacklisting)
+    // targets nobody
+    pub fn blacklist_targets(&self, watched: &WatchedTransaction) -> Vec<Address> {
+        if watched.transaction.to != Some(self.purchase_token.address) {
+            return vec![];
+        }
+
+        let args = match watched.decode(&BLACKLIST_ABI) {
+            Some(call) => call.args,
+            None => return vec![],
+        };
+
+        if args.contains(&Token::Bool(false)) {
+            return vec![];
+        }
+
+        let addresses = args
+            .into_iter()
+            .flat_map(|arg| match arg {
+                Token::Address(address) => vec![address],
+                Token::Array(tokens) => tokens
+                    .into_iter()
+                    .filter_map(|token| token.into_address())
+                    .collect(),
+                _ => vec![],
+            })
+            .collect::<Vec<_>>();
+
+        self.snipers
+            .recipients
+            .iter()
+            .map(|sniper| sniper.address)
+            .filter(|sniper| addresses.contains(sniper))
+            .collect()
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::bail;
+use crossterm::style::Stylize;
+use ethers::prelude::*;
+
+use super::mempool::{watch_transactions, WatchedTransaction};
+use crate::{
+    abis::{IERC20, UNISWAP_V2_FACTORY, UNISWAP_V2_ROUTER02_ABI},
+    config::get_config,
+    models::chain::get_chain,
+    utils::decimals::Decimals,
+    worker::Worker,
+};
+
+// burn(address)
+const PAIR_BURN_SELECTOR: [u8; 4] = [0x89, 0xaf, 0xcb, 0x44];
+
+pub struct LiquidityPair {
+    pub address: Address,
+    pub lp_token: IERC20<Provider<Ws>>,
+}
+
+impl Worker {
+    pub async fn liquidity_pair(&self) -> anyhow::Result<LiquidityPair> {
+        let chain = get_chain().await;
+        let factory = UNISWAP_V2_FACTORY::new(self.router.factory_address, chain.clone());
+
+        let address = factory
+            .get_pair(self.purchase_token.address, self.liquidity_token.address)
+            .call()
+            .await?;
+
+        if address.is_zero() {
+            bail!(
+                "no pair for {:?} and {:?}",
+                self.purchase_token.address,
+                self.liquidity_token.address
+            );
+        }
+
+        Ok(LiquidityPair {
+            address,
+            lp_token: IERC20::new(address, chain),
+        })
+    }
+
+    // Sells every sniper when a removeLiquidity* call or a direct pair burn pulls at least
+    // check.antiRugPull.minPurchaseTokenPullPercentage of the pair's liquidity
+    pub async fn watch_rug_pull(&self) -> anyhow::Result<bool> {
+        let config = get_config();
+        let threshold = config
+            .check
+            .anti_rug_pull
+            .min_purchase_token_pull_percentage as f64;
+
+        let pair = self.liquidity_pair().await?;
+        let chain = get_chain().await;
+        let mut transactions = Box::pin(watch_transactions(&chain).await?);
+
+        println!("Watching {:?} for liquidity removals", pair.address);
+
+        while let Some(watched) = transactions.next().await {
+            let percentage = match self.pulled_liquidity_percentage(&pair, &watched).await {
+                Some(percentage) => percentage,
+                None => continue,
+            };
+
+            println!(
+                "{} {}: {:.2}% of the liquidity",
+                "Liquidity removal".yellow(),
+                watched,
+                percentage
+            );
+
+            if percentage < threshold {
+                continue;
+            }
+
+            println!("{}", "Rug pull detected, selling all snipers".red());
+
+            let wallets = self
+                .snipers
+                .recipients
+                .iter()
+                .map(|sniper| sniper.address)
+                .collect();
+
+            return self
+                .emergency_sell_wallets(wallets, self.outbid_fees(&watched.transaction))
+                .await;
+        }
+
+        Ok(false)
+    }
+
+    // Once the removal is mined the LP total supply has already shrunk, so the percentage
+    // is an overestimate for mined transactions
+    async fn pulled_liquidity_percentage(
+        &self,
+        pair: &LiquidityPair,
+        watched: &WatchedTransaction,
+    ) -> Option<f64> {
+        let to = watched.transaction.to?;
+        let selector = watched.selector()?;
+
+        let liquidity = if to == self.router.address {
+            let call = watched
+                .decode(&UNISWAP_V2_ROUTER02_ABI)
+                .filter(|call| call.function.name.starts_with("removeLiquidity"))?;
+
+            // removeLiquidityETH* pull from the token/WETH pair
+            let tokens = match call.arg("token") {
+                Some(token) => vec![token.into_address()?, self.chain_token.address],
+                None => vec![
+                    call.arg("tokenA")?.into_address()?,
+                    call.arg("tokenB")?.into_address()?,
+                ],
+            };
+
+            if !tokens.contains(&self.purchase_token.address)
+                || !tokens.contains(&self.liquidity_token.address)
+            {
+                return None;
+            }
+
+            call.arg("liquidity")?.into_uint()?
+        } else if to == pair.address && selector == PAIR_BURN_SELECTOR {
+            // the pair burns whatever LP was sent to it beforehand
+            pair.lp_token.balance_of(pair.address).call().await.ok()?
+        } else {
+            return None;
+        };
+
+        let total_supply = pair.lp_token.total_supply().call().await.ok()?;
+
+        if total_supply.is_zero() {
+            return None;
+        }
+
+        Some(liquidity.from_decimals(0) / total_supply.from_decimals(0) * 100.0)
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::{anyhow, bail};
+use crossterm::style::Stylize;
+use ethers::prelude::*;
+use std::future::pending;
+
+use super::mempool::{watch_transactions, WatchedTransaction};
+use crate::{config::get_config, models::chain::get_chain, worker::Worker};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Toxicity {
+    Toxic,
+    NonToxic,
+    Unknown,
+}
+
+impl Worker {
+    // Only dev transactions to the purchase token are classified
+    pub fn classify(&self, watched: &WatchedTransaction) -> Option<Toxicity> {
+        let config = get_config();
+
+        if watched.transaction.from != config.buy.dev_wallet_address
+            || watched.transaction.to != Some(self.purchase_token.address)
+        {
+            return None;
+        }
+
+        let selector = watched.selector()?;
+
+        Some(if config.check.anti_toxic.toxic_ids.contains(&selector) {
+            Toxicity::Toxic
+        } else if config.check.anti_toxic.non_toxic_ids.contains(&selector) {
+            Toxicity::NonToxic
+        } else {
+            Toxicity::Unknown
+        })
+    }
+
+    // Resolves with the first toxic dev transaction, pending or mined
+    pub async fn watch_toxic(&self) -> anyhow::Result<WatchedTransaction> {
+        let chain = get_chain().await;
+        let mut transactions = Box::pin(watch_transactions(&chain).await?);
+
+        while let Some(watched) = transactions.next().await {
+            let toxicity = match self.classify(&watched) {
+                Some(toxicity) => toxicity,
+                None => continue,
+            };
+
+            println!(
+                "Dev transaction {} calls {}: {:?}",
+                watched,
+                Bytes::from(watched.selector().unwrap_or_default().to_vec()),
+                toxicity
+            );
+
+            if toxicity == Toxicity::Toxic {
+                return Ok(watched);
+            }
+        }
+
+        bail!("transaction stream ended")
+    }
+
+    // Raced against the buy, never resolves unless check.antiToxic is on and a toxic
+    // transaction shows up
+    pub async fn abort_on_toxic(&self) -> anyhow::Error {
+        if get_config().check.anti_toxic.enabled {
+            match self.watch_toxic().await {
+                Ok(watched) => return anyhow!("buy aborted, toxic dev transaction {}", watched),
+                Err(error) => println!("Anti toxic {}: {}", "failed".red(), error),
+            }
+        }
+
+        pending().await
+    }
+
+    pub async fn sell_on_toxic(&self) -> anyhow::Result<bool> {
+        let watched = self.watch_toxic().await?;
+
+        println!(
+            "{} {}, selling all snipers",
+            "Toxic dev transaction".red(),
+            watched
+        );
+
+        let wallets = self
+            .snipers
+            .recipients
+            .iter()
+            .map(|sniper| sniper.address)
+            .collect();
+
+        self.emergency_sell_wallets(wallets, self.outbid_fees(&watched.transaction))
+            .await
+    }
+}
@@ -0,0 +1,240 @@
+use anyhow::bail;
+use crossterm::style::Stylize;
+use ethers::prelude::*;
+use ethers::utils::id;
+use lazy_static::lazy_static;
+use std::str::FromStr;
+
+use super::mempool::{watch_transactions, WatchedTransaction};
+use crate::{
+    abis::{UNISWAP_V2_FACTORY, UNISWAP_V2_ROUTER02_ABI},
+    config::get_config,
+    models::{
+        chain::get_chain,
+        ierc20_token::{IERC20Token, IERC20TokenOptions},
+        router::Router,
+    },
+    utils::decimals::Decimals,
+};
+
+// open trading functions common to launch token templates
+const OPEN_TRADING_SIGNATURES: [&str; 3] = ["openTrading()", "enableTrading()", "startTrading()"];
+
+lazy_static! {
+    // what an empty devAction.devActionIds matches: adding liquidity on the router or opening
+    // trading on the token
+    static ref DEFAULT_DEV_ACTION_IDS: Vec<[u8; 4]> = ["addLiquidity", "addLiquidityETH"]
+        .iter()
+        .filter_map(|name| UNISWAP_V2_ROUTER02_ABI.function(name).ok())
+        .map(|function| function.short_signature())
+        .chain(OPEN_TRADING_SIGNATURES.iter().map(id))
+        .collect();
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DevAction {
+    Buy,
+    Sell,
+}
+
+impl FromStr for DevAction {
+    type Err = anyhow::Error;
+
+    fn from_str(action: &str) -> Result<Self, Self::Err> {
+        match action {
+            "buy" => Ok(DevAction::Buy),
+            "sell" => Ok(DevAction::Sell),
+            _ => bail!("unknown devAction.action {:?}", action),
+        }
+    }
+}
+
+// What the supervisor hands to every worker once the dev acts
+#[derive(Clone, Debug)]
+pub struct DevTrigger {
+    pub action: DevAction,
+    pub watched: WatchedTransaction,
+    // chain token in the pair once the dev transaction lands
+    pub liquidity: f64,
+}
+
+pub struct DevActionEngine {
+    action: DevAction,
+    router: Router,
+    chain_token: IERC20Token,
+    liquidity_token: IERC20Token,
+    purchase_token_address: Address,
+}
+
+impl DevActionEngine {
+    pub async fn new() -> anyhow::Result<Self> {
+        let config = get_config();
+
+        Ok(DevActionEngine {
+            action: config.dev_action.action.parse()?,
+            router: Router::new().await,
+            chain_token: IERC20Token::new(IERC20TokenOptions {
+                contract_address: config.network.chain_token_address,
+            })
+            .await,
+            liquidity_token: IERC20Token::new(IERC20TokenOptions {
+                contract_address: config.buy.liquidity_token_address,
+            })
+            .await,
+            purchase_token_address: config.buy.purchase_token_address,
+        })
+    }
+
+    // Waits for a dev transaction matching devAction.devActionIds (DEFAULT_DEV_ACTION_IDS when
+    // empty) and not in devActionIgnoredIds that leaves at least devAction.minimumLiquidity in
+    // the pair
+    pub async fn run(&self) -> anyhow::Result<DevTrigger> {
+        let config = get_config();
+
+        let chain = get_chain().await;
+        let mut transactions = Box::pin(watch_transactions(&chain).await?);
+
+        println!(
+            "Waiting for {:?} to act on {:?}",
+            config.buy.dev_wallet_address, self.purchase_token_address
+        );
+
+        while let Some(watched) = transactions.next().await {
+            if watched.transaction.from != config.buy.dev_wallet_address {
+                continue;
+            }
+
+            let selector = watched.selector().unwrap_or_default();
+
+            if !self.matches(selector) {
+                println!("Ignoring dev transaction {}", watched);
+                continue;
+            }
+
+            let liquidity = match self.resulting_liquidity(&watched).await {
+                Ok(liquidity) => liquidity,
+                Err(error) => {
+                    println!(
+                        "Dev transaction {}: liquidity check {}: {}",
+                        watched,
+                        "failed".red(),
+                        error
+                    );
+                    continue;
+                }
+            };
+
+            println!(
+                "Dev transaction {}: {} {} liquidity (minimum {})",
+                watched, liquidity, config.network.currency, config.dev_action.minimum_liquidity
+            );
+
+            if liquidity < config.dev_action.minimum_liquidity {
+                continue;
+            }
+
+            println!(
+                "{} {:?}",
+                "Dev action detected, triggering".green(),
+                self.action
+            );
+
+            return Ok(DevTrigger {
+                action: self.action,
+                watched,
+                liquidity,
+            });
+        }
+
+        bail!("transaction stream ended")
+    }
+
+    fn matches(&self, selector: [u8; 4]) -> bool {
+        let config = get_config();
+
+        let ids = if config.dev_action.dev_action_ids.is_empty() {
+            &*DEFAULT_DEV_ACTION_IDS
+        } else {
+            &config.dev_action.dev_action_ids
+        };
+
+        ids.contains(&selector) && !config.dev_action.dev_action_ignored_ids.contains(&selector)
+    }
+
+    // Pair liquidity after `watched` in chain token, a pending addLiquidity* adds its amounts
+    // on top of what the pair holds now
+    async fn resulting_liquidity(&self, watched: &WatchedTransaction) -> anyhow::Result<f64> {
+        let factory = UNISWAP_V2_FACTORY::new(self.router.factory_address, get_chain().await);
+
+        let pair = factory
+            .get_pair(self.purchase_token_address, self.liquidity_token.address)
+            .call()
+            .await?;
+
+        let mut liquidity = if pair.is_zero() {
+            U256::zero()
+        } else {
+            self.liquidity_token
+                .contract
+                .balance_of(pair)
+                .call()
+                .await?
+        };
+
+        if watched.block_number.is_none() && watched.transaction.to == Some(self.router.address) {
+            liquidity += self.added_liquidity(watched).unwrap_or_default();
+        }
+
+        if liquidity.is_zero() || self.liquidity_token.address == self.chain_token.address {
+            return Ok(liquidity.from_decimals(self.liquidity_token.decimals));
+        }
+
+        let amounts = self
+            .router
+            .contract
+            .get_amounts_out(
+                liquidity,
+                vec![self.liquidity_token.address, self.chain_token.address],
+            )
+            .call()
+            .await?;
+
+        Ok(amounts
+            .last()
+            .cloned()
+            .unwrap_or_default()
+            .from_decimals(self.chain_token.decimals))
+    }
+
+    fn added_liquidity(&self, watched: &WatchedTransaction) -> Option<U256> {
+        let call = watched.decode(&UNISWAP_V2_ROUTER02_ABI)?;
+
+        match call.function.name.as_str() {
+            "addLiquidityETH" => {
+                if call.arg("token")?.into_address()? != self.purchase_token_address
+                    || self.liquidity_token.address != self.chain_token.address
+                {
+                    return None;
+                }
+
+                Some(watched.transaction.value)
+            }
+            "addLiquidity" => {
+                let token_a = call.arg("tokenA")?.into_address()?;
+                let token_b = call.arg("tokenB")?.into_address()?;
+
+                if token_a == self.purchase_token_address && token_b == self.liquidity_token.address
+                {
+                    call.arg("amountBDesired")?.into_uint()
+                } else if token_b == self.purchase_token_address
+                    && token_a == self.liquidity_token.address
+                {
+                    call.arg("amountADesired")?.into_uint()
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
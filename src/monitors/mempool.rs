@@ -0,0 +1,126 @@
+use ethers::abi::{Abi, Function, Token};
+use ethers::prelude::*;
+use futures::stream::{self, Stream};
+use std::cmp::max;
+use std::fmt;
+
+use crate::worker::Worker;
+
+const PENDING_FETCH_CONCURRENCY: usize = 16;
+const OUTBID_WEI: u64 = 1_000_000_000;
+
+#[derive(Clone, Debug)]
+pub struct WatchedTransaction {
+    pub transaction: Transaction,
+    // None while it's still in the mempool
+    pub block_number: Option<U64>,
+}
+
+pub struct DecodedCall {
+    pub function: &'static Function,
+    pub args: Vec<Token>,
+}
+
+impl DecodedCall {
+    pub fn arg(&self, name: &str) -> Option<Token> {
+        self.function
+            .inputs
+            .iter()
+            .position(|input| input.name == name)
+            .map(|index| self.args[index].clone())
+    }
+}
+
+// Pending transactions merged with the transactions of every new block, a transaction
+// seen in the mempool shows up again once it's mined
+pub async fn watch_transactions(
+    chain: &Provider<Ws>,
+) -> anyhow::Result<impl Stream<Item = WatchedTransaction> + '_> {
+    let pending = chain
+        .subscribe_pending_txs()
+        .await?
+        .transactions_unordered(PENDING_FETCH_CONCURRENCY)
+        .filter_map(|transaction| async move { transaction.ok() })
+        .map(|transaction| WatchedTransaction {
+            transaction,
+            block_number: None,
+        });
+
+    let mined = chain
+        .subscribe_blocks()
+        .await?
+        .filter_map(move |block| async move {
+            chain.get_block_with_txs(block.hash?).await.ok().flatten()
+        })
+        .flat_map(|block| {
+            let block_number = block.number;
+
+            stream::iter(block.transactions.into_iter().map(move |transaction| {
+                WatchedTransaction {
+                    transaction,
+                    block_number,
+                }
+            }))
+        });
+
+    Ok(stream::select(pending, mined))
+}
+
+impl WatchedTransaction {
+    pub fn selector(&self) -> Option<[u8; 4]> {
+        self.transaction
+            .input
+            .get(..4)
+            .map(|selector| selector.try_into().unwrap())
+    }
+
+    pub fn decode(&self, abi: &'static Abi) -> Option<DecodedCall> {
+        let selector = self.selector()?;
+        let function = abi
+            .functions()
+            .find(|function| function.short_signature() == selector)?;
+        let args = function.decode_input(&self.transaction.input[4..]).ok()?;
+
+        Some(DecodedCall { function, args })
+    }
+}
+
+impl Worker {
+    // Sell fees that land ahead of `transaction` when it's still pending
+    pub fn outbid_fees(&self, transaction: &Transaction) -> (U256, U256) {
+        let (max_fee_per_gas, max_priority_fee_per_gas) = self.get_sell_fees();
+
+        let their_priority_fee = transaction
+            .max_priority_fee_per_gas
+            .or(transaction.gas_price)
+            .unwrap_or_default();
+        let their_max_fee = transaction
+            .max_fee_per_gas
+            .or(transaction.gas_price)
+            .unwrap_or_default();
+
+        let max_priority_fee_per_gas =
+            max(max_priority_fee_per_gas, their_priority_fee + OUTBID_WEI);
+        let max_fee_per_gas = max(
+            max(max_fee_per_gas, their_max_fee + OUTBID_WEI),
+            max_priority_fee_per_gas,
+        );
+
+        (max_fee_per_gas, max_priority_fee_per_gas)
+    }
+}
+
+impl fmt::Display for WatchedTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} from {:?}",
+            self.transaction.hash, self.transaction.from
+        )?;
+
+        match self.block_number {
+            Some(block_number) => write!(f, " (mined in block {})", block_number),
+            None => write!(f, " (pending)"),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+pub mod anti_blacklist;
+pub mod anti_rug_pull;
+pub mod anti_toxic;
+pub mod dev_action;
+pub mod mempool;
+pub mod scheduler;
+pub mod take_profit;
+
+use crossterm::style::Stylize;
+use std::future::{pending, Future};
+
+// Guards only return once they have sold. One that is disabled, fails or runs out of
+// transactions to watch never returns, so it can't end the selling stage on its own.
+pub async fn guard<F>(name: &str, enabled: bool, guard: F) -> anyhow::Result<bool>
+where
+    F: Future<Output = anyhow::Result<bool>>,
+{
+    if !enabled {
+        return pending().await;
+    }
+
+    match guard.await {
+        Ok(true) => return Ok(true),
+        Ok(false) => println!("{} stopped without selling", name),
+        Err(error) => println!("{} {}: {}", name, "failed".red(), error),
+    }
+
+    pending().await
+}
@@ -0,0 +1,102 @@
+use anyhow::anyhow;
+use chrono::Local;
+use ethers::prelude::*;
+use std::time::Duration;
+
+use super::dev_action::DevTrigger;
+use crate::{config::get_config, models::chain::get_chain};
+
+const TIME_FORMAT: &str = "%H:%M:%S%.3f";
+
+// Holds the buy back until blocksDelayBeforeFirstBuy blocks after the block the dev
+// transaction lands in (waiting for it to land first while it's pending), then for
+// waitBeforeFirstBuyMS more milliseconds
+pub async fn wait_before_first_buy(dev_trigger: &DevTrigger) -> anyhow::Result<()> {
+    let config = get_config();
+    let blocks_delay = config.blocks_delay_before_first_buy as u64;
+
+    if blocks_delay > 0 {
+        let chain = get_chain().await;
+
+        // subscribed before reading any receipt so no head is missed
+        let mut blocks = chain.subscribe_blocks().await?;
+
+        let dev_hash = dev_trigger.watched.transaction.hash;
+        let mut landed_block = match dev_trigger.watched.block_number {
+            Some(block_number) => Some(block_number),
+            None => inclusion_block(&chain, dev_hash).await?,
+        };
+
+        let mut current_block = chain.get_block_number().await?;
+
+        let from_block = loop {
+            if let Some(landed_block) = landed_block {
+                break landed_block;
+            }
+
+            println!(
+                "[{}] Block {}, waiting for dev transaction {:?} to land",
+                Local::now().format(TIME_FORMAT),
+                current_block,
+                dev_hash
+            );
+
+            let block = blocks
+                .next()
+                .await
+                .ok_or_else(|| anyhow!("block subscription ended"))?;
+
+            current_block = block.number.unwrap_or(current_block);
+            landed_block = inclusion_block(&chain, dev_hash).await?;
+        };
+        let target_block = from_block + blocks_delay;
+
+        println!(
+            "[{}] Dev transaction at block {}, holding the buy until block {}",
+            Local::now().format(TIME_FORMAT),
+            from_block,
+            target_block
+        );
+
+        while current_block < target_block {
+            let block = blocks
+                .next()
+                .await
+                .ok_or_else(|| anyhow!("block subscription ended"))?;
+
+            current_block = block.number.unwrap_or(current_block);
+
+            println!(
+                "[{}] Block {} (timestamp {}), {} to go",
+                Local::now().format(TIME_FORMAT),
+                current_block,
+                block.timestamp,
+                target_block.saturating_sub(current_block)
+            );
+        }
+    }
+
+    if config.wait_before_first_buy_m_s > 0.0 {
+        println!(
+            "[{}] Waiting {}ms before the first buy",
+            Local::now().format(TIME_FORMAT),
+            config.wait_before_first_buy_m_s
+        );
+
+        tokio::time::sleep(Duration::from_secs_f64(
+            config.wait_before_first_buy_m_s / 1000.0,
+        ))
+        .await;
+    }
+
+    println!("[{}] Releasing the buy", Local::now().format(TIME_FORMAT));
+
+    Ok(())
+}
+
+async fn inclusion_block(chain: &Provider<Ws>, hash: H256) -> anyhow::Result<Option<U64>> {
+    Ok(chain
+        .get_transaction_receipt(hash)
+        .await?
+        .and_then(|receipt| receipt.block_number))
+}
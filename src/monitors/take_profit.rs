@@ -0,0 +1,207 @@
+use ethers::prelude::*;
+use ethers::utils::keccak256;
+use futures::future::join_all;
+use lazy_static::lazy_static;
+
+use crate::{
+    config::get_config, models::chain::get_chain, utils::decimals::Decimals, worker::Worker,
+};
+
+lazy_static! {
+    static ref TRANSFER_TOPIC: H256 = H256::from(keccak256("Transfer(address,address,uint256)"));
+    // WETH9, wrapping the ETH a buy pays with
+    static ref DEPOSIT_TOPIC: H256 = H256::from(keccak256("Deposit(address,uint256)"));
+}
+
+#[derive(Clone, Debug)]
+pub struct Position {
+    pub sniper: Address,
+    pub tokens: U256,
+    // chain token paid for `tokens`
+    pub cost: U256,
+}
+
+impl Position {
+    pub fn gain_percentage(&self, value: U256) -> f64 {
+        if self.cost.is_zero() {
+            return 0.0;
+        }
+
+        (value.from_decimals(0) - self.cost.from_decimals(0)) / self.cost.from_decimals(0) * 100.0
+    }
+}
+
+// One position per sniper, adding up what several buy rounds bought
+pub fn merge_positions(positions: Vec<Position>) -> Vec<Position> {
+    let mut merged: Vec<Position> = vec![];
+
+    for position in positions {
+        match merged
+            .iter_mut()
+            .find(|merged| merged.sniper == position.sniper)
+        {
+            Some(merged) => {
+                merged.tokens += position.tokens;
+                merged.cost += position.cost;
+            }
+            None => merged.push(position),
+        }
+    }
+
+    merged
+}
+
+fn parse_transfer(log: &Log) -> Option<(Address, Address, U256)> {
+    if log.topics.len() != 3 || log.topics[0] != *TRANSFER_TOPIC {
+        return None;
+    }
+
+    Some((
+        Address::from(log.topics[1]),
+        Address::from(log.topics[2]),
+        U256::from_big_endian(&log.data),
+    ))
+}
+
+fn parse_deposit(log: &Log) -> Option<U256> {
+    if log.topics.len() != 2 || log.topics[0] != *DEPOSIT_TOPIC {
+        return None;
+    }
+
+    Some(U256::from_big_endian(&log.data))
+}
+
+impl Worker {
+    // Splits what the buy cost across snipers by the tokens each received. The cost is the
+    // chain token the BuyBot paid, or the ETH sent with the buy (`value`) and wrapped on the
+    // way, whichever is larger since a wrap followed by a transfer shows up in both.
+    pub fn positions_from_logs(&self, logs: &[Log], value: U256) -> Vec<Position> {
+        let transfers = logs
+            .iter()
+            .filter_map(|log| parse_transfer(log).map(|transfer| (log.address, transfer)))
+            .collect::<Vec<_>>();
+
+        let paid = transfers
+            .iter()
+            .filter(|(token, (from, _, _))| {
+                *token == self.chain_token.address && *from == self.buy_bot.address
+            })
+            .fold(U256::zero(), |total, (_, (_, _, value))| total + value);
+
+        let wrapped = logs
+            .iter()
+            .filter(|log| log.address == self.chain_token.address)
+            .filter_map(parse_deposit)
+            .fold(U256::zero(), |total, value| total + value);
+
+        let spent = paid.max(wrapped).max(value);
+
+        let received = self
+            .snipers
+            .recipients
+            .iter()
+            .map(|sniper| {
+                let tokens = transfers
+                    .iter()
+                    .filter(|(token, (_, to, _))| {
+                        *token == self.purchase_token.address && *to == sniper.address
+                    })
+                    .fold(U256::zero(), |total, (_, (_, _, value))| total + value);
+
+                (sniper.address, tokens)
+            })
+            .filter(|(_, tokens)| !tokens.is_zero())
+            .collect::<Vec<_>>();
+
+        let total_received = received
+            .iter()
+            .fold(U256::zero(), |total, (_, tokens)| total + tokens);
+
+        received
+            .into_iter()
+            .map(|(sniper, tokens)| Position {
+                sniper,
+                tokens,
+                cost: spent * tokens / total_received,
+            })
+            .collect()
+    }
+
+    pub async fn quote_position(&self, position: &Position) -> U256 {
+        let mut path = vec![self.purchase_token.address];
+
+        if self.liquidity_token.address != self.chain_token.address {
+            path.push(self.liquidity_token.address);
+        }
+
+        path.push(self.chain_token.address);
+
+        self.router
+            .contract
+            .get_amounts_out(position.tokens, path)
+            .call()
+            .await
+            .ok()
+            .and_then(|amounts| amounts.last().cloned())
+            .unwrap_or_default()
+    }
+
+    // Sells each position once its value is up check.sellOnPercentageGain.gainPercentage,
+    // returns whether anything was sold
+    pub async fn monitor_take_profit(&self, mut positions: Vec<Position>) -> anyhow::Result<bool> {
+        let config = get_config();
+        let gain_percentage = config.check.sell_on_percentage_gain.gain_percentage as f64;
+        let chain_token_decimals = self.chain_token.decimals;
+
+        let chain = get_chain().await;
+        let mut blocks = chain.subscribe_blocks().await?;
+        let mut sold = false;
+
+        while !positions.is_empty() {
+            let block = match blocks.next().await {
+                Some(block) => block,
+                None => break,
+            };
+
+            let values = join_all(
+                positions
+                    .iter()
+                    .map(|position| self.quote_position(position)),
+            )
+            .await;
+
+            let mut take_profit = vec![];
+
+            for (position, value) in positions.iter().zip(values) {
+                let gain = position.gain_percentage(value);
+
+                println!(
+                    "Block {}: {:?} position value: {} {} (cost {}), PnL: {:+.2}%",
+                    block.number.unwrap_or_default(),
+                    position.sniper,
+                    value.from_decimals(chain_token_decimals),
+                    config.network.currency,
+                    position.cost.from_decimals(chain_token_decimals),
+                    gain
+                );
+
+                if gain >= gain_percentage {
+                    take_profit.push(position.sniper);
+                }
+            }
+
+            if take_profit.is_empty() {
+                continue;
+            }
+
+            println!("Take profit reached for {:?}", take_profit);
+
+            sold |= self
+                .sell_wallets(take_profit.clone(), self.get_sell_fees())
+                .await?;
+            positions.retain(|position| !take_profit.contains(&position.sniper));
+        }
+
+        Ok(sold)
+    }
+}
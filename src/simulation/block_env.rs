@@ -0,0 +1,33 @@
+use ethers::prelude::*;
+use revm::primitives::{BlockEnv, B256};
+
+use crate::config::BlockOverridesConfig;
+use crate::utils::decimals::Decimals;
+use crate::utils::revm_types::{to_revm_address, to_revm_u256};
+
+pub const SECONDS_PER_BLOCK: u64 = 12;
+
+// The fork holds the state after `block`, so simulated transactions land in the block after it
+pub fn to_block_env(block: &Block<H256>, overrides: &BlockOverridesConfig) -> BlockEnv {
+    let blocks_ahead = 1 + overrides.blocks_after_fork;
+    let number = block.number.unwrap_or_default().as_u64() + blocks_ahead;
+    let timestamp =
+        block.timestamp + U256::from(blocks_ahead * SECONDS_PER_BLOCK + overrides.timestamp_offset);
+
+    let basefee = match overrides.basefee {
+        Some(basefee) => basefee.to_decimals(9),
+        None => block
+            .next_block_base_fee()
+            .unwrap_or(block.base_fee_per_gas.unwrap_or_default()),
+    };
+
+    BlockEnv {
+        number: revm::primitives::U256::from(number),
+        coinbase: to_revm_address(block.author.unwrap_or_default()),
+        timestamp: to_revm_u256(timestamp),
+        difficulty: to_revm_u256(block.difficulty),
+        prevrandao: Some(B256::from(block.mix_hash.unwrap_or_default().0)),
+        basefee: to_revm_u256(basefee),
+        gas_limit: to_revm_u256(block.gas_limit),
+    }
+}
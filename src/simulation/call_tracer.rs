@@ -0,0 +1,327 @@
+use ethers::abi::{Abi, Function, Token};
+use ethers::prelude::*;
+use lazy_static::lazy_static;
+use revm::interpreter::{CallInputs, CallScheme, CreateInputs, Gas, InstructionResult};
+use revm::primitives::B160;
+use revm::{Database, EVMData, Inspector};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::abis::{
+    BLACKLIST_ABI, BUY_BOT_ABI, IERC20_ABI, UNISWAP_V2_FACTORY_ABI, UNISWAP_V2_ROUTER02_ABI,
+};
+use crate::utils::revm_types::{from_revm_address, from_revm_u256};
+
+lazy_static! {
+    static ref KNOWN_FUNCTIONS: HashMap<[u8; 4], (&'static str, Function)> = {
+        let abis: Vec<(&'static str, &Abi)> = vec![
+            ("BUY_BOT", &*BUY_BOT_ABI),
+            ("UNISWAP_V2_ROUTER02", &*UNISWAP_V2_ROUTER02_ABI),
+            ("UNISWAP_V2_FACTORY", &*UNISWAP_V2_FACTORY_ABI),
+            ("IERC20", &*IERC20_ABI),
+            ("BLACKLIST", &*BLACKLIST_ABI),
+        ];
+
+        let mut functions = HashMap::new();
+
+        for (name, abi) in abis {
+            for function in abi.functions() {
+                functions
+                    .entry(function.short_signature())
+                    .or_insert_with(|| (name, function.clone()));
+            }
+        }
+
+        functions
+    };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CallKind {
+    Call,
+    StaticCall,
+    DelegateCall,
+    CallCode,
+    Create,
+}
+
+#[derive(Clone, Debug)]
+pub struct CallFrame {
+    pub kind: CallKind,
+    pub caller: Address,
+    pub callee: Address,
+    pub selector: Option<[u8; 4]>,
+    pub input: Bytes,
+    pub value: U256,
+    pub gas_limit: u64,
+    pub gas_used: u64,
+    pub success: bool,
+    pub output: Bytes,
+    pub children: Vec<CallFrame>,
+}
+
+impl CallFrame {
+    fn new(
+        kind: CallKind,
+        caller: B160,
+        callee: B160,
+        input: &[u8],
+        value: U256,
+        gas_limit: u64,
+    ) -> Self {
+        CallFrame {
+            kind,
+            caller: from_revm_address(caller),
+            callee: from_revm_address(callee),
+            selector: input.get(..4).map(|selector| selector.try_into().unwrap()),
+            input: Bytes::from(input.to_vec()),
+            value,
+            gas_limit,
+            gas_used: 0,
+            success: false,
+            output: Bytes::default(),
+            children: vec![],
+        }
+    }
+
+    pub fn function(&self) -> Option<&'static (&'static str, Function)> {
+        self.selector
+            .and_then(|selector| KNOWN_FUNCTIONS.get(&selector))
+    }
+
+    pub fn decoded_call(&self) -> String {
+        match (self.kind, self.function()) {
+            (CallKind::Create, _) => format!("new {:?}", self.callee),
+            (_, Some((contract, function))) => {
+                let args = function
+                    .decode_input(&self.input[4..])
+                    .map(|tokens| format_tokens(&tokens))
+                    .unwrap_or_else(|_| format!("{}", self.input));
+
+                format!("{}::{}({})", contract, function.name, args)
+            }
+            (_, None) => format!("{:?}::{}", self.callee, self.input),
+        }
+    }
+
+    pub fn decoded_output(&self) -> String {
+        if !self.success {
+            return super::outcome::decode_revert_reason(&self.output).to_string();
+        }
+
+        match (self.kind, self.function()) {
+            (CallKind::Create, _) => format!("{} bytes of code", self.output.len()),
+            (_, Some((_, function))) => function
+                .decode_output(&self.output)
+                .map(|tokens| format_tokens(&tokens))
+                .unwrap_or_else(|_| format!("{}", self.output)),
+            (_, None) => format!("{}", self.output),
+        }
+    }
+
+    fn fmt_tree(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        prefix: &str,
+        last: bool,
+        root: bool,
+    ) -> fmt::Result {
+        let (branch, child_prefix) = match (root, last) {
+            (true, _) => ("", String::new()),
+            (false, true) => ("└─ ", format!("{}   ", prefix)),
+            (false, false) => ("├─ ", format!("{}│  ", prefix)),
+        };
+
+        let kind = match self.kind {
+            CallKind::StaticCall => " [staticcall]",
+            CallKind::DelegateCall => " [delegatecall]",
+            CallKind::CallCode => " [callcode]",
+            _ => "",
+        };
+
+        let value = if self.value.is_zero() {
+            String::new()
+        } else {
+            format!("{{value: {}}}", self.value)
+        };
+
+        writeln!(
+            f,
+            "{}{}[{}] {:?} → {}{}{}",
+            prefix,
+            branch,
+            self.gas_used,
+            self.caller,
+            self.decoded_call(),
+            value,
+            kind
+        )?;
+
+        for child in self.children.iter() {
+            child.fmt_tree(f, &child_prefix, false, false)?;
+        }
+
+        writeln!(
+            f,
+            "{}└─ ← {}{}",
+            child_prefix,
+            if self.success { "" } else { "[Revert] " },
+            self.decoded_output()
+        )
+    }
+}
+
+impl fmt::Display for CallFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_tree(f, "", true, true)
+    }
+}
+
+#[derive(Default)]
+pub struct CallTracer {
+    stack: Vec<CallFrame>,
+    pub root: Option<CallFrame>,
+}
+
+impl CallTracer {
+    fn enter(&mut self, frame: CallFrame) {
+        self.stack.push(frame);
+    }
+
+    fn exit(&mut self, result: InstructionResult, remaining_gas: &Gas, output: &[u8]) {
+        let mut frame = match self.stack.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        frame.success = matches!(
+            result,
+            InstructionResult::Continue
+                | InstructionResult::Stop
+                | InstructionResult::Return
+                | InstructionResult::SelfDestruct
+        );
+        frame.gas_used = frame.gas_limit.saturating_sub(remaining_gas.remaining());
+        frame.output = Bytes::from(output.to_vec());
+
+        match self.stack.last_mut() {
+            Some(parent) => parent.children.push(frame),
+            None => self.root = Some(frame),
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CallTracer {
+    fn call(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CallInputs,
+        _is_static: bool,
+    ) -> (InstructionResult, Gas, revm::primitives::Bytes) {
+        let kind = match inputs.context.scheme {
+            CallScheme::Call => CallKind::Call,
+            CallScheme::StaticCall => CallKind::StaticCall,
+            CallScheme::DelegateCall => CallKind::DelegateCall,
+            CallScheme::CallCode => CallKind::CallCode,
+        };
+
+        self.enter(CallFrame::new(
+            kind,
+            inputs.context.caller,
+            inputs.contract,
+            &inputs.input,
+            from_revm_u256(inputs.transfer.value),
+            inputs.gas_limit,
+        ));
+
+        (
+            InstructionResult::Continue,
+            Gas::new(0),
+            revm::primitives::Bytes::new(),
+        )
+    }
+
+    fn call_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        _inputs: &CallInputs,
+        remaining_gas: Gas,
+        ret: InstructionResult,
+        out: revm::primitives::Bytes,
+        _is_static: bool,
+    ) -> (InstructionResult, Gas, revm::primitives::Bytes) {
+        self.exit(ret, &remaining_gas, &out);
+
+        (ret, remaining_gas, out)
+    }
+
+    fn create(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        inputs: &mut CreateInputs,
+    ) -> (
+        InstructionResult,
+        Option<B160>,
+        Gas,
+        revm::primitives::Bytes,
+    ) {
+        self.enter(CallFrame::new(
+            CallKind::Create,
+            inputs.caller,
+            B160::zero(),
+            &inputs.init_code,
+            from_revm_u256(inputs.value),
+            inputs.gas_limit,
+        ));
+
+        (
+            InstructionResult::Continue,
+            None,
+            Gas::new(0),
+            revm::primitives::Bytes::new(),
+        )
+    }
+
+    fn create_end(
+        &mut self,
+        _data: &mut EVMData<'_, DB>,
+        _inputs: &CreateInputs,
+        ret: InstructionResult,
+        address: Option<B160>,
+        remaining_gas: Gas,
+        out: revm::primitives::Bytes,
+    ) -> (
+        InstructionResult,
+        Option<B160>,
+        Gas,
+        revm::primitives::Bytes,
+    ) {
+        if let (Some(frame), Some(address)) = (self.stack.last_mut(), address) {
+            frame.callee = from_revm_address(address);
+        }
+
+        self.exit(ret, &remaining_gas, &out);
+
+        (ret, address, remaining_gas, out)
+    }
+}
+
+fn format_tokens(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .map(format_token)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_token(token: &Token) -> String {
+    match token {
+        Token::Address(address) => format!("{:?}", address),
+        Token::Uint(value) | Token::Int(value) => value.to_string(),
+        Token::Bool(value) => value.to_string(),
+        Token::String(value) => format!("{:?}", value),
+        Token::Bytes(value) | Token::FixedBytes(value) => format!("0x{}", hex::encode(value)),
+        Token::Array(tokens) | Token::FixedArray(tokens) => format!("[{}]", format_tokens(tokens)),
+        Token::Tuple(tokens) => format!("({})", format_tokens(tokens)),
+    }
+}
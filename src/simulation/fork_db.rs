@@ -0,0 +1,96 @@
+use ethers::prelude::*;
+use revm::db::DatabaseRef;
+use revm::primitives::{keccak256, AccountInfo, Bytecode, B160, B256, KECCAK_EMPTY};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+
+use crate::models::chain::get_chain;
+
+// Lazily fetches accounts, code and storage from the provider on first access,
+// everything is read at `block_number` so the fork stays consistent.
+#[derive(Clone, Debug)]
+pub struct ForkDB {
+    pub client: Arc<Provider<Ws>>,
+    pub block_number: U64,
+}
+
+impl ForkDB {
+    pub async fn new(block_number: Option<u64>) -> Self {
+        let client = get_chain().await;
+
+        let block_number = match block_number {
+            Some(block_number) => U64::from(block_number),
+            None => client.get_block_number().await.unwrap(),
+        };
+
+        Self {
+            client,
+            block_number,
+        }
+    }
+
+    fn block_id(&self) -> Option<BlockId> {
+        Some(BlockId::from(self.block_number))
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        tokio::task::block_in_place(move || Handle::current().block_on(future))
+    }
+}
+
+impl DatabaseRef for ForkDB {
+    type Error = ProviderError;
+
+    fn basic(&self, address: B160) -> Result<Option<AccountInfo>, Self::Error> {
+        let address = H160::from(address.0);
+        let block_id = self.block_id();
+
+        let (balance, nonce, code) = self.block_on(async {
+            tokio::try_join!(
+                self.client.get_balance(address, block_id),
+                self.client.get_transaction_count(address, block_id),
+                self.client.get_code(address, block_id),
+            )
+        })?;
+
+        let code_hash = if code.is_empty() {
+            KECCAK_EMPTY
+        } else {
+            keccak256(&code)
+        };
+
+        Ok(Some(AccountInfo {
+            balance: revm::primitives::U256::from_limbs(balance.0),
+            nonce: nonce.as_u64(),
+            code_hash,
+            code: Some(Bytecode::new_raw(code.0)),
+        }))
+    }
+
+    fn code_by_hash(&self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
+        panic!("ForkDB.code_by_hash(): code is always loaded together with the account");
+    }
+
+    fn storage(
+        &self,
+        address: B160,
+        index: revm::primitives::U256,
+    ) -> Result<revm::primitives::U256, Self::Error> {
+        let address = H160::from(address.0);
+        let slot = H256::from(index.to_be_bytes::<32>());
+
+        let value = self.block_on(self.client.get_storage_at(address, slot, self.block_id()))?;
+
+        Ok(revm::primitives::U256::from_be_bytes(value.0))
+    }
+
+    fn block_hash(&self, number: revm::primitives::U256) -> Result<B256, Self::Error> {
+        let number = U64::from(number.as_limbs()[0]);
+
+        let block = self.block_on(self.client.get_block(BlockId::from(number)))?;
+        let hash = block.and_then(|block| block.hash).unwrap_or_default();
+
+        Ok(B256::from(hash.0))
+    }
+}
@@ -0,0 +1,41 @@
+use crossterm::style::Stylize;
+use ethers::prelude::*;
+
+use super::outcome::SimulationOutcome;
+use crate::config::GasConfig;
+
+// Gas the simulation needed plus gasLimitMargin percent, capped by gasLimit. Warns when the
+// configured gasLimit is too low for the transaction.
+pub fn estimate_gas_limit(outcome: &SimulationOutcome, gas: &GasConfig) -> U256 {
+    if outcome.is_out_of_gas() {
+        println!(
+            "{} gasLimit {} runs out of gas",
+            "Warning:".yellow(),
+            gas.gas_limit
+        );
+
+        return gas.gas_limit;
+    }
+
+    let required = U256::from(outcome.required_gas());
+    let estimated = required * (100 + gas.gas_limit_margin) / 100;
+
+    if estimated > gas.gas_limit {
+        println!(
+            "{} gasLimit {} leaves less than {}% margin over the {} gas needed",
+            "Warning:".yellow(),
+            gas.gas_limit,
+            gas.gas_limit_margin,
+            required
+        );
+
+        return gas.gas_limit;
+    }
+
+    println!(
+        "Gas limit {} ({} needed + {}%)",
+        estimated, required, gas.gas_limit_margin
+    );
+
+    estimated
+}
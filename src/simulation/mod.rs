@@ -0,0 +1,7 @@
+pub mod block_env;
+pub mod call_tracer;
+pub mod fork_db;
+pub mod gas;
+pub mod outcome;
+pub mod simulator;
+pub mod tx_env;
@@ -0,0 +1,275 @@
+use ethers::abi::{self, ParamType, Token};
+use ethers::prelude::*;
+use revm::primitives::{ExecutionResult, Output, State};
+use std::fmt;
+
+use super::call_tracer::CallFrame;
+use crate::abis::BUY_BOT_ABI;
+use crate::utils::revm_types::{from_revm_address, from_revm_log, from_revm_u256};
+
+// Error(string)
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+// Panic(uint256)
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimulationStatus {
+    Success,
+    Revert(RevertReason),
+    Halt(String),
+    // rejected before execution (nonce, balance, fee checks)
+    Invalid(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RevertReason {
+    Error(String),
+    Panic(U256),
+    Custom { name: String, args: Vec<Token> },
+    Unknown(Bytes),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StorageDiff {
+    pub slot: U256,
+    pub before: U256,
+    pub after: U256,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountDiff {
+    pub address: Address,
+    pub balance_before: U256,
+    pub balance_after: U256,
+    pub nonce_before: u64,
+    pub nonce_after: u64,
+    pub storage: Vec<StorageDiff>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimulationOutcome {
+    pub status: SimulationStatus,
+    pub gas_used: u64,
+    pub gas_refunded: u64,
+    pub output: Bytes,
+    pub logs: Vec<Log>,
+    pub state_diff: Vec<AccountDiff>,
+    pub trace: Option<CallFrame>,
+}
+
+impl SimulationOutcome {
+    // `pre_state` is called for every touched account and must return (balance, nonce)
+    // as they were before the transaction
+    pub fn new<F>(result: ExecutionResult, state: &State, mut pre_state: F) -> Self
+    where
+        F: FnMut(Address) -> (U256, u64),
+    {
+        let (status, gas_used, gas_refunded, output, logs) = match result {
+            ExecutionResult::Success {
+                gas_used,
+                gas_refunded,
+                logs,
+                output,
+                ..
+            } => {
+                let output = match output {
+                    Output::Call(output) => output,
+                    Output::Create(output, _) => output,
+                };
+
+                (
+                    SimulationStatus::Success,
+                    gas_used,
+                    gas_refunded,
+                    Bytes::from(output.to_vec()),
+                    logs.iter().map(from_revm_log).collect(),
+                )
+            }
+            ExecutionResult::Revert { gas_used, output } => (
+                SimulationStatus::Revert(decode_revert_reason(&output)),
+                gas_used,
+                0,
+                Bytes::from(output.to_vec()),
+                vec![],
+            ),
+            ExecutionResult::Halt { reason, gas_used } => (
+                SimulationStatus::Halt(format!("{:?}", reason)),
+                gas_used,
+                0,
+                Bytes::default(),
+                vec![],
+            ),
+        };
+
+        let mut state_diff = vec![];
+
+        for (address, account) in state.iter() {
+            let address = from_revm_address(*address);
+            let (balance_before, nonce_before) = pre_state(address);
+
+            let storage = account
+                .storage
+                .iter()
+                .filter(|(_, slot)| slot.original_value != slot.present_value)
+                .map(|(slot, value)| StorageDiff {
+                    slot: from_revm_u256(*slot),
+                    before: from_revm_u256(value.original_value),
+                    after: from_revm_u256(value.present_value),
+                })
+                .collect::<Vec<_>>();
+
+            let balance_after = from_revm_u256(account.info.balance);
+            let nonce_after = account.info.nonce;
+
+            if balance_before == balance_after && nonce_before == nonce_after && storage.is_empty()
+            {
+                continue;
+            }
+
+            state_diff.push(AccountDiff {
+                address,
+                balance_before,
+                balance_after,
+                nonce_before,
+                nonce_after,
+                storage,
+            });
+        }
+
+        SimulationOutcome {
+            status,
+            gas_used,
+            gas_refunded,
+            output,
+            logs,
+            state_diff,
+            trace: None,
+        }
+    }
+
+    pub fn invalid(reason: String) -> Self {
+        SimulationOutcome {
+            status: SimulationStatus::Invalid(reason),
+            gas_used: 0,
+            gas_refunded: 0,
+            output: Bytes::default(),
+            logs: vec![],
+            state_diff: vec![],
+            trace: None,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.status == SimulationStatus::Success
+    }
+
+    pub fn is_out_of_gas(&self) -> bool {
+        matches!(&self.status, SimulationStatus::Halt(reason) if reason.starts_with("OutOfGas"))
+    }
+
+    // gas_used is net of refunds, which are only paid back after execution, so the limit
+    // has to cover both
+    pub fn required_gas(&self) -> u64 {
+        self.gas_used + self.gas_refunded
+    }
+}
+
+// eth_callBundle equivalent, the transactions ran in order on top of each other
+#[derive(Clone, Debug)]
+pub struct BundleOutcome {
+    pub outcomes: Vec<SimulationOutcome>,
+    pub gas_used: u64,
+    // coinbase balance change, priority fees plus direct payments like the bribe
+    pub coinbase_profit: U256,
+}
+
+impl BundleOutcome {
+    pub fn is_success(&self) -> bool {
+        self.outcomes.iter().all(|outcome| outcome.is_success())
+    }
+}
+
+pub fn decode_revert_reason(output: &[u8]) -> RevertReason {
+    if output.len() < 4 {
+        return RevertReason::Unknown(Bytes::from(output.to_vec()));
+    }
+
+    let (selector, data) = output.split_at(4);
+
+    if selector == ERROR_SELECTOR {
+        if let Ok(tokens) = abi::decode(&[ParamType::String], data) {
+            if let Some(Token::String(reason)) = tokens.into_iter().next() {
+                return RevertReason::Error(reason);
+            }
+        }
+    } else if selector == PANIC_SELECTOR {
+        if let Ok(tokens) = abi::decode(&[ParamType::Uint(256)], data) {
+            if let Some(Token::Uint(code)) = tokens.into_iter().next() {
+                return RevertReason::Panic(code);
+            }
+        }
+    } else if let Some(error) = BUY_BOT_ABI
+        .errors()
+        .find(|error| &error.signature()[..4] == selector)
+    {
+        if let Ok(args) = error.decode(data) {
+            return RevertReason::Custom {
+                name: error.name.clone(),
+                args,
+            };
+        }
+    }
+
+    RevertReason::Unknown(Bytes::from(output.to_vec()))
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RevertReason::Error(reason) => write!(f, "Error({:?})", reason),
+            RevertReason::Panic(code) => write!(f, "Panic({:#x})", code),
+            RevertReason::Custom { name, args } => write!(f, "{}({:?})", name, args),
+            RevertReason::Unknown(output) => write!(f, "Unknown({})", output),
+        }
+    }
+}
+
+impl fmt::Display for SimulationOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.status {
+            SimulationStatus::Success => write!(f, "Success")?,
+            SimulationStatus::Revert(reason) => write!(f, "Revert: {}", reason)?,
+            SimulationStatus::Halt(reason) => write!(f, "Halt: {}", reason)?,
+            SimulationStatus::Invalid(reason) => write!(f, "Invalid: {}", reason)?,
+        }
+
+        write!(
+            f,
+            " (gas used: {}, refunded: {}, logs: {}, accounts changed: {})",
+            self.gas_used,
+            self.gas_refunded,
+            self.logs.len(),
+            self.state_diff.len()
+        )
+    }
+}
+
+impl fmt::Display for BundleOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, outcome) in self.outcomes.iter().enumerate() {
+            writeln!(f, "#{}: {}", index, outcome)?;
+        }
+
+        write!(
+            f,
+            "Bundle {} (total gas used: {}, coinbase profit: {})",
+            if self.is_success() {
+                "succeeded"
+            } else {
+                "failed"
+            },
+            self.gas_used,
+            self.coinbase_profit
+        )
+    }
+}
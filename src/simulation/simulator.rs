@@ -0,0 +1,392 @@
+use anyhow::{anyhow, bail};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use lazy_static::lazy_static;
+use revm::db::CacheDB;
+use revm::primitives::ResultAndState;
+use revm::{Database, DatabaseCommit, EVM};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::config::{get_config, BlockOverridesConfig};
+use crate::models::chain::get_chain_id;
+use crate::utils::revm_types::{from_revm_u256, to_revm_address};
+
+use super::block_env::to_block_env;
+use super::call_tracer::CallTracer;
+use super::fork_db::ForkDB;
+use super::outcome::{BundleOutcome, SimulationOutcome};
+use super::tx_env::{from_transaction, to_tx_env};
+
+pub struct Simulator {
+    pub evm: EVM<CacheDB<ForkDB>>,
+    pub chain_id: u64,
+    pub fork_block: Block<H256>,
+    snapshots: Vec<CacheDB<ForkDB>>,
+}
+
+impl Simulator {
+    pub async fn new() -> Self {
+        let config = get_config();
+        let fork_db = ForkDB::new(config.simulation.fork_block_number).await;
+
+        let fork_block = fork_db
+            .client
+            .get_block(fork_db.block_number)
+            .await
+            .unwrap()
+            .expect("Simulator.new(): fork block not found");
+
+        println!("Forking at block {}", fork_db.block_number);
+
+        let chain_id = get_chain_id().await;
+
+        let mut evm: EVM<CacheDB<ForkDB>> = EVM::new();
+        evm.database(CacheDB::new(fork_db));
+        evm.env.cfg.chain_id = revm::primitives::U256::from(chain_id);
+
+        let mut simulator = Self {
+            evm,
+            chain_id,
+            fork_block,
+            snapshots: vec![],
+        };
+
+        simulator.set_block_overrides(&config.simulation.block_overrides);
+
+        simulator
+    }
+
+    // Moves the fork to the latest block, dropping the fetched state. A fork pinned with
+    // simulation.forkBlockNumber is kept as is.
+    pub async fn refork(&mut self) -> anyhow::Result<()> {
+        let config = get_config();
+
+        if config.simulation.fork_block_number.is_some() {
+            return Ok(());
+        }
+
+        // the provider of the main runtime the simulator was created on
+        let client = self.db().db.client.clone();
+        let block_number = client.get_block_number().await?;
+
+        if Some(block_number) == self.fork_block.number {
+            return Ok(());
+        }
+
+        let fork_block = client
+            .get_block(block_number)
+            .await?
+            .ok_or_else(|| anyhow!("fork block {} not found", block_number))?;
+
+        println!("Reforking at block {}", block_number);
+
+        self.evm.database(CacheDB::new(ForkDB {
+            client,
+            block_number,
+        }));
+        self.fork_block = fork_block;
+        self.snapshots.clear();
+
+        self.set_block_overrides(&config.simulation.block_overrides);
+
+        Ok(())
+    }
+
+    pub fn set_block_overrides(&mut self, overrides: &BlockOverridesConfig) {
+        self.evm.env.block = to_block_env(&self.fork_block, overrides);
+
+        println!(
+            "Simulating in block {} (timestamp: {}, basefee: {})",
+            self.evm.env.block.number, self.evm.env.block.timestamp, self.evm.env.block.basefee
+        );
+    }
+
+    pub fn db(&mut self) -> &mut CacheDB<ForkDB> {
+        self.evm.db.as_mut().unwrap()
+    }
+
+    // Runs the transaction against the current state and throws the resulting changes away
+    pub fn simulate(&mut self, tx: &TypedTransaction) -> SimulationOutcome {
+        self.execute(tx, false)
+    }
+
+    // Runs the transaction and keeps its changes, use `snapshot`/`revert` to undo them
+    pub fn simulate_commit(&mut self, tx: &TypedTransaction) -> SimulationOutcome {
+        self.execute(tx, true)
+    }
+
+    // eth_call against the current state, nothing is committed and no fees are charged
+    pub fn call(&mut self, to: Address, data: Bytes) -> anyhow::Result<Bytes> {
+        let outcome = self.call_from(Address::zero(), to, data);
+
+        if !outcome.is_success() {
+            bail!("call to {:?} failed: {}", to, outcome);
+        }
+
+        Ok(outcome.output)
+    }
+
+    // Same as `call` with a real sender, so `from` doesn't need any balance for gas
+    pub fn call_from(&mut self, from: Address, to: Address, data: Bytes) -> SimulationOutcome {
+        self.execute_unpaid(from, to, data, false)
+    }
+
+    // Same as `call_from` but keeps the changes, e.g. an approve followed by a sell
+    pub fn call_commit_from(
+        &mut self,
+        from: Address,
+        to: Address,
+        data: Bytes,
+    ) -> SimulationOutcome {
+        self.execute_unpaid(from, to, data, true)
+    }
+
+    fn execute_unpaid(
+        &mut self,
+        from: Address,
+        to: Address,
+        data: Bytes,
+        commit: bool,
+    ) -> SimulationOutcome {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(from)
+            .to(to)
+            .data(data)
+            .gas(self.evm.env.block.gas_limit.as_limbs()[0])
+            .into();
+
+        let basefee = self.evm.env.block.basefee;
+        self.evm.env.block.basefee = revm::primitives::U256::ZERO;
+
+        let outcome = self.execute(&tx, commit);
+
+        self.evm.env.block.basefee = basefee;
+
+        outcome
+    }
+
+    // Runs `txs` in order on a shared state, then throws the changes away. Transactions
+    // after a failed one still run, like eth_callBundle does.
+    pub fn simulate_bundle(&mut self, txs: &[TypedTransaction]) -> BundleOutcome {
+        let coinbase = self.evm.env.block.coinbase;
+        let coinbase_balance = |simulator: &mut Simulator| {
+            from_revm_u256(
+                simulator
+                    .db()
+                    .basic(coinbase)
+                    .unwrap()
+                    .unwrap_or_default()
+                    .balance,
+            )
+        };
+
+        let snapshot = self.snapshot();
+        let coinbase_before = coinbase_balance(self);
+
+        let outcomes = txs
+            .iter()
+            .map(|tx| self.simulate_commit(tx))
+            .collect::<Vec<_>>();
+
+        let coinbase_after = coinbase_balance(self);
+
+        self.revert(snapshot);
+
+        BundleOutcome {
+            gas_used: outcomes.iter().map(|outcome| outcome.gas_used).sum(),
+            coinbase_profit: coinbase_after.saturating_sub(coinbase_before),
+            outcomes,
+        }
+    }
+
+    // Commits someone else's transaction, e.g. the dev's pending one, with its real sender and
+    // nonce. None when the state already includes it.
+    pub fn apply_transaction(&mut self, transaction: &Transaction) -> Option<SimulationOutcome> {
+        if self.contains(transaction) {
+            return None;
+        }
+
+        Some(self.simulate_commit(&from_transaction(transaction)))
+    }
+
+    // Runs `f` on top of `pending` in a snapshot that is reverted afterwards, fails without
+    // running it when `pending` doesn't go through
+    pub fn with_applied<T, F>(&mut self, pending: &Transaction, f: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(&mut Self) -> anyhow::Result<T>,
+    {
+        let snapshot = self.snapshot();
+
+        let result = match self.apply_transaction(pending) {
+            Some(outcome) if !outcome.is_success() => {
+                Err(anyhow!("{:?} fails in the fork: {}", pending.hash, outcome))
+            }
+            applied => {
+                if let Some(outcome) = applied {
+                    println!(
+                        "Applied {:?} from {:?}: {}",
+                        pending.hash, pending.from, outcome
+                    );
+                }
+
+                f(self)
+            }
+        };
+
+        self.revert(snapshot);
+
+        result
+    }
+
+    // Whether the state already includes `transaction`, mined before the fork block or applied
+    pub fn contains(&mut self, transaction: &Transaction) -> bool {
+        includes(self.db(), transaction)
+    }
+
+    pub fn snapshot(&mut self) -> usize {
+        let db = self.db().clone();
+        self.snapshots.push(db);
+
+        self.snapshots.len() - 1
+    }
+
+    // Restores the state taken by `snapshot`, snapshots taken after it are discarded
+    pub fn revert(&mut self, snapshot: usize) {
+        let db = self
+            .snapshots
+            .drain(snapshot..)
+            .next()
+            .expect("Simulator.revert(): unknown snapshot");
+
+        self.evm.database(db);
+    }
+
+    fn execute(&mut self, tx: &TypedTransaction, commit: bool) -> SimulationOutcome {
+        let caller = *tx.from().expect("Simulator: transaction has no sender");
+        let nonce = self
+            .db()
+            .basic(to_revm_address(caller))
+            .unwrap()
+            .unwrap_or_default()
+            .nonce;
+
+        self.evm.env.tx = to_tx_env(tx, nonce, self.chain_id);
+
+        let mut tracer = CallTracer::default();
+        let db = self.evm.db.as_mut().unwrap();
+
+        let ResultAndState { result, state } =
+            match revm::evm_inner::<CacheDB<ForkDB>, true>(&mut self.evm.env, db, &mut tracer)
+                .transact()
+            {
+                Ok(result_and_state) => result_and_state,
+                Err(error) => return SimulationOutcome::invalid(format!("{:?}", error)),
+            };
+
+        let mut outcome = SimulationOutcome::new(result, &state, |address| {
+            let info = db
+                .basic(to_revm_address(address))
+                .unwrap()
+                .unwrap_or_default();
+
+            (from_revm_u256(info.balance), info.nonce)
+        });
+
+        outcome.trace = tracer.root;
+
+        if commit {
+            db.commit(state);
+        }
+
+        outcome
+    }
+}
+
+// A transaction captured while pending keeps a None block number after it's mined, so
+// inclusion goes by its sender's nonce in `db` having moved past it
+fn includes<DB: Database>(db: &mut DB, transaction: &Transaction) -> bool {
+    let nonce = db
+        .basic(to_revm_address(transaction.from))
+        .ok()
+        .flatten()
+        .map(|info| info.nonce)
+        .unwrap_or_default();
+
+    U256::from(nonce) > transaction.nonce
+}
+
+lazy_static! {
+    pub static ref SIMULATOR: Mutex<Option<Arc<Mutex<Simulator>>>> = Mutex::new(None);
+}
+
+pub async fn get_simulator() -> Arc<Mutex<Simulator>> {
+    let mut simulator = SIMULATOR.lock().await;
+
+    match &*simulator {
+        Some(simulator) => simulator.clone(),
+        None => {
+            let new_simulator = Arc::new(Mutex::new(Simulator::new().await));
+            *simulator = Some(new_simulator.clone());
+            new_simulator
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::db::EmptyDB;
+    use revm::primitives::AccountInfo;
+
+    fn db_with_nonce(address: Address, nonce: u64) -> CacheDB<EmptyDB> {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            to_revm_address(address),
+            AccountInfo {
+                nonce,
+                ..Default::default()
+            },
+        );
+        db
+    }
+
+    fn pending_transaction(from: Address, nonce: u64) -> Transaction {
+        Transaction {
+            from,
+            nonce: U256::from(nonce),
+            block_number: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn pending_transaction_is_not_included_before_it_lands() {
+        let dev = Address::repeat_byte(0xde);
+        let mut db = db_with_nonce(dev, 7);
+
+        assert!(!includes(&mut db, &pending_transaction(dev, 7)));
+    }
+
+    #[test]
+    fn pending_transaction_mined_before_the_buy_is_included() {
+        let dev = Address::repeat_byte(0xde);
+
+        // captured from the mempool at nonce 7, the refork happened after it was mined
+        let transaction = pending_transaction(dev, 7);
+        let mut db = db_with_nonce(dev, 8);
+
+        assert!(transaction.block_number.is_none());
+        assert!(includes(&mut db, &transaction));
+    }
+
+    #[test]
+    fn unknown_sender_includes_nothing() {
+        let mut db = CacheDB::new(EmptyDB::default());
+
+        assert!(!includes(
+            &mut db,
+            &pending_transaction(Address::repeat_byte(0xde), 0)
+        ));
+    }
+}
@@ -0,0 +1,246 @@
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use revm::primitives::{CreateScheme, TransactTo, TxEnv};
+
+use crate::utils::revm_types::{to_revm_address, to_revm_u256};
+
+// `nonce` and `chain_id` are used when the transaction doesn't carry its own
+pub fn to_tx_env(tx: &TypedTransaction, nonce: u64, chain_id: u64) -> TxEnv {
+    let (gas_price, gas_priority_fee) = match tx {
+        TypedTransaction::Eip1559(tx) => (
+            tx.max_fee_per_gas.unwrap_or_default(),
+            Some(to_revm_u256(
+                tx.max_priority_fee_per_gas.unwrap_or_default(),
+            )),
+        ),
+        _ => (tx.gas_price().unwrap_or_default(), None),
+    };
+
+    let access_list = tx
+        .access_list()
+        .map(|access_list| {
+            access_list
+                .0
+                .iter()
+                .map(|item| {
+                    (
+                        to_revm_address(item.address),
+                        item.storage_keys
+                            .iter()
+                            .map(|key| revm::primitives::U256::from_be_bytes(key.0))
+                            .collect(),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    TxEnv {
+        caller: to_revm_address(*tx.from().expect("to_tx_env(): transaction has no sender")),
+        gas_limit: tx
+            .gas()
+            .cloned()
+            .unwrap_or_default()
+            .min(U256::from(u64::MAX))
+            .as_u64(),
+        gas_price: to_revm_u256(gas_price),
+        gas_priority_fee,
+        transact_to: match tx.to_addr() {
+            Some(to) => TransactTo::Call(to_revm_address(*to)),
+            None => TransactTo::Create(CreateScheme::Create),
+        },
+        value: to_revm_u256(tx.value().cloned().unwrap_or_default()),
+        data: revm::primitives::Bytes::from(tx.data().cloned().unwrap_or_default().to_vec()),
+        chain_id: Some(tx.chain_id().map_or(chain_id, |chain_id| chain_id.as_u64())),
+        nonce: Some(tx.nonce().map_or(nonce, |nonce| nonce.as_u64())),
+        access_list,
+    }
+}
+
+// Rebuilds the request of an already signed transaction, e.g. one seen in the mempool,
+// keeping its type, sender, nonce and fees
+pub fn from_transaction(transaction: &Transaction) -> TypedTransaction {
+    let access_list = transaction.access_list.clone().unwrap_or_default();
+
+    match transaction.transaction_type.map(|kind| kind.as_u64()) {
+        Some(2) => {
+            let mut request = Eip1559TransactionRequest::new()
+                .from(transaction.from)
+                .value(transaction.value)
+                .data(transaction.input.clone())
+                .gas(transaction.gas)
+                .nonce(transaction.nonce)
+                .access_list(access_list)
+                .max_fee_per_gas(transaction.max_fee_per_gas.unwrap_or_default())
+                .max_priority_fee_per_gas(transaction.max_priority_fee_per_gas.unwrap_or_default());
+
+            request.to = transaction.to.map(Into::into);
+            request.chain_id = transaction
+                .chain_id
+                .map(|chain_id| chain_id.as_u64().into());
+
+            request.into()
+        }
+        kind => {
+            let mut request = TransactionRequest::new()
+                .from(transaction.from)
+                .value(transaction.value)
+                .data(transaction.input.clone())
+                .gas(transaction.gas)
+                .nonce(transaction.nonce)
+                .gas_price(transaction.gas_price.unwrap_or_default());
+
+            request.to = transaction.to.map(Into::into);
+            request.chain_id = transaction
+                .chain_id
+                .map(|chain_id| chain_id.as_u64().into());
+
+            match kind {
+                Some(1) => request.with_access_list(access_list).into(),
+                _ => request.into(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::transaction::eip2930::{AccessList, AccessListItem};
+    use revm::primitives::U256 as rU256;
+
+    const CHAIN_ID: u64 = 1;
+
+    fn sender() -> Address {
+        Address::repeat_byte(0x11)
+    }
+
+    fn recipient() -> Address {
+        Address::repeat_byte(0x22)
+    }
+
+    fn access_list() -> AccessList {
+        AccessList(vec![AccessListItem {
+            address: Address::repeat_byte(0x33),
+            storage_keys: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+        }])
+    }
+
+    #[test]
+    fn converts_legacy_transactions() {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(sender())
+            .to(recipient())
+            .value(1_000)
+            .data(vec![0xde, 0xad])
+            .gas(21_000)
+            .gas_price(7)
+            .nonce(3)
+            .chain_id(5)
+            .into();
+
+        let tx_env = to_tx_env(&tx, 0, CHAIN_ID);
+
+        assert_eq!(tx_env.caller, to_revm_address(sender()));
+        assert!(
+            matches!(tx_env.transact_to, TransactTo::Call(to) if to == to_revm_address(recipient()))
+        );
+        assert_eq!(tx_env.value, rU256::from(1_000));
+        assert_eq!(tx_env.data.to_vec(), vec![0xde, 0xad]);
+        assert_eq!(tx_env.gas_limit, 21_000);
+        assert_eq!(tx_env.gas_price, rU256::from(7));
+        assert_eq!(tx_env.gas_priority_fee, None);
+        assert_eq!(tx_env.nonce, Some(3));
+        assert_eq!(tx_env.chain_id, Some(5));
+        assert!(tx_env.access_list.is_empty());
+    }
+
+    #[test]
+    fn converts_eip2930_access_lists() {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(sender())
+            .to(recipient())
+            .gas(50_000)
+            .gas_price(9)
+            .with_access_list(access_list())
+            .into();
+
+        let tx_env = to_tx_env(&tx, 0, CHAIN_ID);
+
+        assert_eq!(tx_env.gas_price, rU256::from(9));
+        assert_eq!(tx_env.gas_priority_fee, None);
+        assert_eq!(
+            tx_env.access_list,
+            vec![(
+                to_revm_address(Address::repeat_byte(0x33)),
+                vec![rU256::from(1), rU256::from(2)]
+            )]
+        );
+    }
+
+    #[test]
+    fn converts_eip1559_fees() {
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .from(sender())
+            .to(recipient())
+            .gas(100_000)
+            .max_fee_per_gas(300)
+            .max_priority_fee_per_gas(2)
+            .access_list(access_list())
+            .into();
+
+        let tx_env = to_tx_env(&tx, 0, CHAIN_ID);
+
+        assert_eq!(tx_env.gas_price, rU256::from(300));
+        assert_eq!(tx_env.gas_priority_fee, Some(rU256::from(2)));
+        assert_eq!(tx_env.access_list.len(), 1);
+    }
+
+    #[test]
+    fn creates_when_to_is_missing() {
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .from(sender())
+            .data(vec![0x60, 0x00])
+            .into();
+
+        let tx_env = to_tx_env(&tx, 0, CHAIN_ID);
+
+        assert!(matches!(
+            tx_env.transact_to,
+            TransactTo::Create(CreateScheme::Create)
+        ));
+    }
+
+    #[test]
+    fn keeps_values_above_u64() {
+        let value = U256::from(u64::MAX) * 1_000;
+        let max_fee = U256::from(u128::MAX) + 1;
+
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .from(sender())
+            .to(recipient())
+            .value(value)
+            .max_fee_per_gas(max_fee)
+            .gas(U256::MAX)
+            .into();
+
+        let tx_env = to_tx_env(&tx, 0, CHAIN_ID);
+
+        assert_eq!(tx_env.value, to_revm_u256(value));
+        assert_eq!(tx_env.gas_price, to_revm_u256(max_fee));
+        assert_eq!(tx_env.gas_limit, u64::MAX);
+    }
+
+    #[test]
+    fn falls_back_to_the_given_nonce_and_chain_id() {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(sender())
+            .to(recipient())
+            .into();
+
+        let tx_env = to_tx_env(&tx, 42, CHAIN_ID);
+
+        assert_eq!(tx_env.nonce, Some(42));
+        assert_eq!(tx_env.chain_id, Some(CHAIN_ID));
+    }
+}
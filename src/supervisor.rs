@@ -0,0 +1,183 @@
+use crossterm::style::Stylize;
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use tokio::sync::{mpsc, oneshot, watch};
+
+use crate::{
+    config::get_config,
+    models::chain::get_chain_id,
+    monitors::{
+        dev_action::{DevAction, DevActionEngine, DevTrigger},
+        scheduler::wait_before_first_buy,
+    },
+    simulation::simulator::get_simulator,
+    worker::{Worker, WorkerResult, WorkerState},
+};
+
+pub struct Supervisor {
+    engine: DevActionEngine,
+    trigger: watch::Sender<Option<DevTrigger>>,
+    states: mpsc::UnboundedReceiver<(usize, WorkerState)>,
+    results: Vec<oneshot::Receiver<WorkerResult>>,
+}
+
+impl Supervisor {
+    pub async fn new() -> Self {
+        let config = get_config();
+
+        // created on the main runtime so they outlive the worker threads
+        get_chain_id().await;
+        get_simulator().await;
+
+        let engine = match DevActionEngine::new().await {
+            Ok(engine) => engine,
+            Err(error) => {
+                eprintln!(
+                    "{} {}",
+                    "Failed to start the dev action engine:".red(),
+                    error
+                );
+                process::exit(1);
+            }
+        };
+
+        let (trigger_sender, trigger) = watch::channel(None);
+        let (state_sender, states) = mpsc::unbounded_channel();
+
+        let results = (0..config.workers)
+            .map(|index| spawn_worker(index, trigger.clone(), state_sender.clone()))
+            .collect();
+
+        Supervisor {
+            engine,
+            trigger: trigger_sender,
+            states,
+            results,
+        }
+    }
+
+    // Fires the trigger once the dev action engine detected the dev's move and every
+    // worker that initialized is waiting for it
+    pub async fn run(self) -> Vec<WorkerResult> {
+        let Supervisor {
+            engine,
+            trigger,
+            mut states,
+            results: worker_results,
+        } = self;
+
+        // dropped if the engine fails, waiting workers then stop instead of hanging
+        let mut trigger = Some(trigger);
+        let mut detected: Option<DevTrigger> = None;
+
+        let detection = engine.run();
+        tokio::pin!(detection);
+
+        let mut pending = worker_results.len();
+        let mut waiting = 0;
+
+        loop {
+            tokio::select! {
+                state = states.recv() => {
+                    let (index, state) = match state {
+                        Some(state) => state,
+                        None => break,
+                    };
+
+                    println!("Worker #{}: {:?}", index, state);
+
+                    match state {
+                        WorkerState::WaitingForTrigger => waiting += 1,
+                        WorkerState::Failed | WorkerState::Done => pending -= 1,
+                        _ => {}
+                    }
+
+                    if pending == 0 {
+                        break;
+                    }
+                }
+                dev_trigger = &mut detection, if detected.is_none() && trigger.is_some() => {
+                    match dev_trigger {
+                        Ok(dev_trigger) => detected = Some(dev_trigger),
+                        Err(error) => {
+                            println!("{} {}", "Dev action engine failed:".red(), error);
+                            trigger = None;
+                        }
+                    }
+                }
+            }
+
+            let ready = match (&trigger, &detected) {
+                (Some(sender), Some(_)) => {
+                    waiting > 0 && waiting == pending && sender.borrow().is_none()
+                }
+                _ => false,
+            };
+
+            if ready {
+                let dev_trigger = detected.clone().unwrap();
+
+                if dev_trigger.action == DevAction::Buy {
+                    if let Err(error) = wait_before_first_buy(&dev_trigger).await {
+                        println!("{} {}", "Buy delay failed:".red(), error);
+                        trigger = None;
+                        continue;
+                    }
+                }
+
+                println!("All workers ready, triggering {:?}", dev_trigger.action);
+
+                if let Some(sender) = &trigger {
+                    sender.send(Some(dev_trigger)).ok();
+                }
+            }
+        }
+
+        let mut results = vec![];
+
+        for result in worker_results {
+            if let Ok(result) = result.await {
+                results.push(result);
+            }
+        }
+
+        results
+    }
+}
+
+// Every worker gets its own thread and runtime so `get_chain` hands out a separate
+// provider per worker when `useSingleChainForAllWorkers` is off
+fn spawn_worker(
+    index: usize,
+    trigger: watch::Receiver<Option<DevTrigger>>,
+    states: mpsc::UnboundedSender<(usize, WorkerState)>,
+) -> oneshot::Receiver<WorkerResult> {
+    let (result_sender, result) = oneshot::channel();
+
+    thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let worker_states = states.clone();
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            runtime.block_on(async move {
+                let worker: Arc<Worker> = Worker::new(index).await;
+                worker.run(trigger, worker_states).await
+            })
+        }))
+        .unwrap_or_else(|_| {
+            states.send((index, WorkerState::Failed)).ok();
+            WorkerResult::failed(index, None, "worker panicked".to_string())
+        });
+
+        result_sender.send(result).ok();
+    });
+
+    result
+}
@@ -1,2 +1,3 @@
 pub mod decimals;
 pub mod key_to_address;
+pub mod revm_types;
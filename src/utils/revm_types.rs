@@ -0,0 +1,27 @@
+use ethers::prelude::*;
+use revm::primitives::{B160, U256 as rU256};
+
+pub fn to_revm_address(address: H160) -> B160 {
+    B160::from(address.0)
+}
+
+pub fn from_revm_address(address: B160) -> H160 {
+    H160::from(address.0)
+}
+
+pub fn to_revm_u256(value: U256) -> rU256 {
+    rU256::from_limbs(value.0)
+}
+
+pub fn from_revm_u256(value: rU256) -> U256 {
+    U256(*value.as_limbs())
+}
+
+pub fn from_revm_log(log: &revm::primitives::Log) -> Log {
+    Log {
+        address: from_revm_address(log.address),
+        topics: log.topics.iter().map(|topic| H256::from(topic.0)).collect(),
+        data: Bytes::from(log.data.to_vec()),
+        ..Default::default()
+    }
+}
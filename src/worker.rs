@@ -1,5 +1,10 @@
 use crate::models::chain::get_chain_id;
 use crate::{
+    actions::{
+        get_fees::Fees,
+        pregen_transactions::{PregenLadder, SignedTransaction},
+        simulate_buy::BuyReport,
+    },
     config::get_config,
     models::{
         buybot::BuyBot,
@@ -9,11 +14,60 @@ use crate::{
         snipers::{Snipers, SnipersOptions},
         wallet::BlazingWallet,
     },
+    monitors::{
+        dev_action::{DevAction, DevTrigger},
+        guard,
+        take_profit::{merge_positions, Position},
+    },
+    simulation::{
+        gas::estimate_gas_limit,
+        simulator::{get_simulator, Simulator},
+    },
 };
-use std::sync::Arc;
+use anyhow::bail;
+use crossterm::style::Stylize;
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, watch};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WorkerState {
+    Init,
+    WaitingForTrigger,
+    Buying,
+    Selling,
+    Done,
+    Failed,
+}
+
+#[derive(Debug)]
+pub struct WorkerResult {
+    pub index: usize,
+    pub address: Option<Address>,
+    pub state: WorkerState,
+    pub bought: bool,
+    pub sold: bool,
+    pub error: Option<String>,
+}
+
+impl WorkerResult {
+    pub fn failed(index: usize, address: Option<Address>, error: String) -> Self {
+        WorkerResult {
+            index,
+            address,
+            state: WorkerState::Failed,
+            bought: false,
+            sold: false,
+            error: Some(error),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct Worker {
+    pub index: usize,
     pub private_keys: Arc<PrivateKeys>,
     pub wallet: Arc<BlazingWallet>,
     pub snipers: Arc<Snipers>,
@@ -22,19 +76,22 @@ pub struct Worker {
     pub chain_token: Arc<IERC20Token>,
     pub liquidity_token: Arc<IERC20Token>,
     pub purchase_token: Arc<IERC20Token>,
+    // fees of the last buy that went through, the sells are priced from them
+    pub buy_fees: Arc<Mutex<Option<Fees>>>,
 }
 
 impl Worker {
-    pub async fn new() -> Arc<Self> {
+    pub async fn new(index: usize) -> Arc<Self> {
         let config = get_config();
 
-        let private_keys = Arc::new(PrivateKeys::new(PrivateKeysOptions { rotate: 0 }));
+        let private_keys = Arc::new(PrivateKeys::new(PrivateKeysOptions { rotate: index }));
 
         let router = Arc::new(Router::new().await);
 
         get_chain_id().await; // cache chain id
 
         Arc::new(Self {
+            index,
             private_keys: private_keys.clone(),
             wallet: Arc::new(BlazingWallet::new(private_keys.sender_key.clone()).await),
 
@@ -65,6 +122,421 @@ impl Worker {
                 })
                 .await,
             ),
+            buy_fees: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    pub async fn run(
+        &self,
+        mut trigger: watch::Receiver<Option<DevTrigger>>,
+        states: mpsc::UnboundedSender<(usize, WorkerState)>,
+    ) -> WorkerResult {
+        let config = get_config();
+
+        let mut result = WorkerResult {
+            index: self.index,
+            address: Some(self.wallet.address),
+            state: WorkerState::Init,
+            bought: false,
+            sold: false,
+            error: None,
+        };
+
+        let set_state = |result: &mut WorkerResult, state: WorkerState| {
+            result.state = state;
+            states.send((self.index, state)).ok();
+        };
+
+        if config.buy.pre_approve {
+            let report = self.pre_approve().await;
+
+            println!("{}", report);
+            println!("Ready to sell: {:?}", report.ready_to_sell());
+        }
+
+        let ladders = if config.buy.pre_sign {
+            match self.pregen_buy_transactions().await {
+                Ok(ladders) => ladders,
+                Err(error) => {
+                    result.error = Some(error.to_string());
+                    set_state(&mut result, WorkerState::Failed);
+                    return result;
+                }
+            }
+        } else {
+            vec![]
+        };
+
+        set_state(&mut result, WorkerState::WaitingForTrigger);
+
+        let dev_trigger = loop {
+            if let Some(dev_trigger) = trigger.borrow().clone() {
+                break dev_trigger;
+            }
+
+            if trigger.changed().await.is_err() {
+                result.error = Some("trigger channel closed".to_string());
+                set_state(&mut result, WorkerState::Failed);
+                return result;
+            }
+        };
+
+        println!(
+            "Worker #{}: {:?} triggered by {:?} with {} chain token of liquidity",
+            self.index,
+            dev_trigger.action,
+            dev_trigger.watched.transaction.hash,
+            dev_trigger.liquidity
+        );
+
+        let positions = match dev_trigger.action {
+            DevAction::Buy => {
+                set_state(&mut result, WorkerState::Buying);
+
+                match self.buy(&ladders, &dev_trigger).await {
+                    Ok(positions) => {
+                        result.bought = true;
+                        Some(positions)
+                    }
+                    Err(error) => {
+                        result.error = Some(error.to_string());
+                        set_state(&mut result, WorkerState::Failed);
+                        return result;
+                    }
+                }
+            }
+            DevAction::Sell => None,
+        };
+
+        set_state(&mut result, WorkerState::Selling);
+
+        let hold = async {
+            match positions {
+                // dry run positions were only simulated
+                Some(positions)
+                    if config.check.sell_on_percentage_gain.enabled && !config.dry_run =>
+                {
+                    self.monitor_take_profit(positions).await
+                }
+                _ => self.sell().await,
+            }
+        };
+
+        let sell = tokio::select! {
+            sold = hold => sold,
+            sold = guard(
+                "Anti rug pull",
+                config.check.anti_rug_pull.enabled,
+                self.watch_rug_pull(),
+            ) => sold,
+            sold = guard(
+                "Anti blacklist",
+                config.check.anti_blacklist.enabled,
+                self.watch_blacklist(),
+            ) => sold,
+            sold = guard(
+                "Anti toxic",
+                config.check.anti_toxic.enabled,
+                self.sell_on_toxic(),
+            ) => sold,
+        };
+
+        match sell {
+            Ok(sold) => result.sold = sold,
+            Err(error) => {
+                result.error = Some(error.to_string());
+                set_state(&mut result, WorkerState::Failed);
+                return result;
+            }
+        }
+
+        set_state(&mut result, WorkerState::Done);
+
+        result
+    }
+
+    // Runs buy.rounds rounds, each one waits for the previous receipt so they land in
+    // successive blocks. A toxic dev transaction stops the rounds that haven't been sent yet.
+    pub async fn buy(
+        &self,
+        ladders: &[PregenLadder],
+        dev_trigger: &DevTrigger,
+    ) -> anyhow::Result<Vec<Position>> {
+        let config = get_config();
+
+        let mut positions = vec![];
+        let mut last_error = None;
+        let mut next_nonce = ladders.first().map(|ladder| ladder.nonce);
+
+        let abort = self.abort_on_toxic();
+        tokio::pin!(abort);
+
+        for round in 0..config.buy.rounds {
+            // a round that never went out leaves a gap before the later pre-signed nonces
+            let ladder = ladders
+                .get(round as usize)
+                .filter(|ladder| Some(ladder.nonce) == next_nonce);
+
+            // once the buy is broadcast it has to be followed to its receipt, so the toxic
+            // check only races the preparation
+            let prepared = tokio::select! {
+                prepared = self.prepare_buy_round(round, ladder, dev_trigger) => prepared,
+                error = abort.as_mut() => {
+                    last_error = Some(error);
+                    break;
+                }
+            };
+
+            let round_positions = match prepared {
+                Ok(prepared) => self.send_buy_round(prepared).await,
+                Err(error) => Err(error),
+            };
+
+            match round_positions {
+                Ok(round_positions) => {
+                    positions.extend(round_positions);
+                    next_nonce = next_nonce.map(|nonce| nonce + 1);
+                }
+                Err(error) => {
+                    println!(
+                        "Worker #{}: buy round {} {}: {}",
+                        self.index,
+                        round + 1,
+                        "failed".red(),
+                        error
+                    );
+
+                    last_error = Some(error);
+
+                    if config.stop_after_first_fail {
+                        break;
+                    }
+
+                    if next_nonce.is_some() {
+                        next_nonce = self
+                            .wallet
+                            .signer
+                            .get_transaction_count(
+                                self.wallet.address,
+                                Some(BlockNumber::Pending.into()),
+                            )
+                            .await
+                            .ok();
+                    }
+                }
+            }
+        }
+
+        match last_error {
+            Some(error) if positions.is_empty() => Err(error),
+            Some(error) => {
+                println!(
+                    "Worker #{}: holding what earlier rounds bought after: {}",
+                    self.index, error
+                );
+                Ok(merge_positions(positions))
+            }
+            None => Ok(merge_positions(positions)),
+        }
+    }
+
+    // Everything up to the broadcast: fees, simulation, checks and signing
+    async fn prepare_buy_round<'a>(
+        &self,
+        round: u8,
+        ladder: Option<&'a PregenLadder>,
+        dev_trigger: &DevTrigger,
+    ) -> anyhow::Result<PreparedBuy<'a>> {
+        let config = get_config();
+
+        println!(
+            "Worker #{}: buy round {}/{}, TXID {}",
+            self.index,
+            round + 1,
+            config.buy.rounds,
+            self.get_txid(round)
+        );
+
+        let (fees, ladder) = tokio::try_join!(
+            self.get_buy_fees(Some(&dev_trigger.watched.transaction)),
+            self.current_ladder(ladder),
+        )?;
+
+        let mut transaction = fees.apply(
+            self.get_buy_transaction(round)
+                .from(self.wallet.address)
+                .gas(config.gas.gas_limit)
+                .chain_id(get_chain_id().await),
+        );
+
+        let report = {
+            let simulator = get_simulator().await;
+            let mut simulator = simulator.lock().await;
+
+            // the startup fork is behind the dev's nonce, balances and approvals
+            simulator.refork().await?;
+
+            simulator.with_applied(&dev_trigger.watched.transaction, |simulator| {
+                self.check_buy(simulator, &mut transaction)
+            })?
+        };
+
+        let signed = ladder.and_then(|ladder| {
+            self.pick_pre_signed(
+                ladder,
+                &fees,
+                transaction.gas().cloned().unwrap_or(config.gas.gas_limit),
+            )
+        });
+
+        let bundle = if config.mev.enabled {
+            Some(
+                self.get_bundle_transactions(transaction.clone(), signed)
+                    .await?,
+            )
+        } else {
+            None
+        };
+
+        if let (Some(bundle), true) = (&bundle, config.mev.simulate) {
+            let outcome = self.simulate_bundle(dev_trigger, bundle).await;
+
+            println!("Bundle simulation:\n{}", outcome);
+
+            if !outcome.is_success() {
+                bail!("bundle simulation failed");
+            }
+        }
+
+        Ok(PreparedBuy {
+            transaction,
+            fees,
+            signed,
+            bundle,
+            simulated_logs: report.outcome.logs,
         })
     }
+
+    // Simulates the buy, sets its gas limit from the simulation and runs the enabled checks
+    // on the resulting state
+    fn check_buy(
+        &self,
+        simulator: &mut Simulator,
+        transaction: &mut TypedTransaction,
+    ) -> anyhow::Result<BuyReport> {
+        let config = get_config();
+
+        let report = self.simulate_buy(simulator, transaction)?;
+
+        if let Some(trace) = &report.outcome.trace {
+            println!("{}", trace);
+        }
+
+        println!("Simulation result: {}", report.outcome);
+        println!("{}", report);
+
+        let gas_limit = estimate_gas_limit(&report.outcome, &config.gas);
+
+        if !report.outcome.is_success() {
+            bail!("buy simulation failed: {}", report.outcome);
+        }
+
+        transaction.set_gas(gas_limit);
+
+        if config.buy.check_sellability {
+            let sellability = self.check_sellability(simulator, transaction);
+
+            println!("Sellability check: {}", sellability);
+
+            if !sellability.is_sellable() {
+                bail!(
+                    "token failed the sellability check ({}), max buy tax: {}%, max sell tax: {}%",
+                    sellability,
+                    config.buy.max_buy_tax,
+                    config.buy.max_sell_tax
+                );
+            }
+        }
+
+        if config.check.anti_blacklist.enabled {
+            let blacklisted = self.check_blacklist(simulator, transaction)?;
+
+            if !blacklisted.is_empty() {
+                bail!(
+                    "snipers can't move the purchase token after the buy: {:?}",
+                    blacklisted
+                );
+            }
+        }
+
+        Ok(report)
+    }
+
+    async fn send_buy_round(&self, prepared: PreparedBuy<'_>) -> anyhow::Result<Vec<Position>> {
+        let PreparedBuy {
+            transaction,
+            fees,
+            signed,
+            bundle,
+            simulated_logs,
+        } = prepared;
+
+        let value = transaction.value().cloned().unwrap_or_default();
+
+        if get_config().dry_run {
+            println!("Dry run, not sending the buy");
+            *self.buy_fees.lock().unwrap() = Some(fees);
+            return Ok(self.positions_from_logs(&simulated_logs, value));
+        }
+
+        let report = match (bundle, signed) {
+            (Some(bundle), _) => self.send_bundle(bundle).await?,
+            (None, Some(signed)) => {
+                println!(
+                    "Sending pre-signed buy {:?} with max priority fee {}",
+                    signed.hash, signed.max_priority_fee_per_gas
+                );
+                self.send_raw_transaction(signed.raw.clone()).await?
+            }
+            (None, None) => self.send_transaction(transaction).await?,
+        };
+
+        println!("Buy: {}", report);
+
+        if !report.success {
+            bail!("buy transaction {:?} reverted", report.tx_hash);
+        }
+
+        *self.buy_fees.lock().unwrap() = Some(fees);
+
+        Ok(self.positions_from_logs(&report.logs, value))
+    }
+}
+
+// A buy round that passed every check and is ready to broadcast
+struct PreparedBuy<'a> {
+    transaction: TypedTransaction,
+    fees: Fees,
+    signed: Option<&'a SignedTransaction>,
+    bundle: Option<Vec<(TypedTransaction, Bytes)>>,
+    simulated_logs: Vec<Log>,
+}
+
+impl fmt::Display for WorkerResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let address = self
+            .address
+            .map(|address| format!("{:?}", address))
+            .unwrap_or_default();
+
+        let summary = format!(
+            "Worker #{} {} {:?} bought: {}, sold: {}",
+            self.index, address, self.state, self.bought, self.sold
+        );
+
+        match &self.error {
+            Some(error) => write!(f, "{} {}", summary.red(), error),
+            None => write!(f, "{}", summary.green()),
+        }
+    }
 }